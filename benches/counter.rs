@@ -0,0 +1,104 @@
+//! Compares updating a bar through its [`ThreadedBarWrapper`] with updating it through a [`ProgressCounter`]
+//!
+//! run with `cargo bench --bench counter`
+//!
+//! [`ThreadedBarWrapper`]: stati::ThreadedBarWrapper
+//! [`ProgressCounter`]: stati::ProgressCounter
+
+use std::hint::black_box;
+use std::thread;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use stati::bars::SimpleBar;
+use stati::prelude::*;
+use stati::{BarManager, RenderMode};
+
+const ITEMS: usize = 10_000;
+
+fn manager() -> BarManager<'static> {
+    BarManager::with_writer(std::io::sink()).render_mode(RenderMode::Plain)
+}
+
+fn single_thread(c: &mut Criterion) {
+    let mut group = c.benchmark_group("single thread");
+    group.throughput(Throughput::Elements(ITEMS as u64));
+
+    group.bench_function("wrapper", |b| {
+        let mut manager = manager();
+        let mut bar = manager.register_threadsafe(SimpleBar::new("bench", ITEMS));
+        b.iter(|| {
+            for i in 0..ITEMS {
+                bar.bar().set_progress(black_box(i));
+            }
+        });
+    });
+
+    group.bench_function("counter", |b| {
+        let mut manager = manager();
+        let mut bar = manager.register_threadsafe(SimpleBar::new("bench", ITEMS));
+        let counter = bar.bar().counter();
+        b.iter(|| {
+            for _ in 0..ITEMS {
+                counter.inc(black_box(1));
+            }
+        });
+    });
+
+    group.finish();
+}
+
+fn many_threads(c: &mut Criterion) {
+    let mut group = c.benchmark_group("many threads");
+    for threads in [2, 4, 8] {
+        group.throughput(Throughput::Elements((ITEMS * threads) as u64));
+
+        group.bench_with_input(
+            BenchmarkId::new("wrapper", threads),
+            &threads,
+            |b, &threads| {
+                let mut manager = manager();
+                let bar = manager.register_threadsafe(SimpleBar::new("bench", ITEMS * threads));
+                // dropping a wrapper finishes the bar, so the clones have to outlive the measurement
+                let mut bars = vec![bar; threads];
+                b.iter(|| {
+                    thread::scope(|scope| {
+                        for bar in &mut bars {
+                            scope.spawn(move || {
+                                for i in 0..ITEMS {
+                                    bar.bar().set_progress(black_box(i));
+                                }
+                            });
+                        }
+                    });
+                });
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("counter", threads),
+            &threads,
+            |b, &threads| {
+                let mut manager = manager();
+                let mut bar = manager.register_threadsafe(SimpleBar::new("bench", ITEMS * threads));
+                let counter = bar.bar().counter();
+                b.iter(|| {
+                    thread::scope(|scope| {
+                        for _ in 0..threads {
+                            let counter = counter.clone();
+                            scope.spawn(move || {
+                                for _ in 0..ITEMS {
+                                    counter.inc(black_box(1));
+                                }
+                            });
+                        }
+                    });
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, single_thread, many_threads);
+criterion_main!(benches);
@@ -1,4 +1,10 @@
-use std::time::Instant;
+use std::time::Duration;
+
+use super::job::Job;
+use super::layout::{fill, indeterminate, pad, Align, Layout, Part};
+use crate::format::{self, HumanDuration, Units};
+use crate::style::{BarStyles, FillColor};
+use crate::{FinishState, ProgressState};
 
 mod default {
     pub const FILLED: &str = "=";
@@ -8,6 +14,10 @@ mod default {
     pub const UNIT: &str = "its";
 }
 
+/// The strings a [`CustomBar`] is drawn with
+///
+/// besides the [default](BarElements::default) elements, there are presets for [smooth](BarElements::smooth)
+/// bars using unicode block elements, and an [ascii](BarElements::ascii) version of that for terminals without unicode
 #[derive(Clone, Debug, Hash)]
 pub struct BarElements {
     filled: String,
@@ -15,6 +25,40 @@ pub struct BarElements {
     start: String,
     end: String,
     unit: String,
+    partials: Vec<String>,
+    fill_color: Option<FillColor>,
+}
+
+impl BarElements {
+    /// Elements for a bar that fills smoothly, using block elements to show how full the cell at its edge is
+    ///
+    /// ```rust
+    /// use stati::bars::custom::{BarElements, Builder};
+    ///
+    /// # fn main() {
+    /// let bar = Builder::new("Smooth").elems(BarElements::smooth()).build();
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn smooth() -> Self {
+        Self {
+            filled: "█".into(),
+            empty: " ".into(),
+            partials: "▏▎▍▌▋▊▉█".chars().map(String::from).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Like [`smooth`](Self::smooth), but only using ASCII characters
+    #[must_use]
+    pub fn ascii() -> Self {
+        Self {
+            filled: "#".into(),
+            empty: " ".into(),
+            partials: vec!["-".into(), "=".into(), "#".into()],
+            ..Self::default()
+        }
+    }
 }
 
 impl Default for BarElements {
@@ -26,111 +70,199 @@ impl Default for BarElements {
             start: START.to_string(),
             end: END.to_string(),
             unit: UNIT.to_string(),
+            partials: vec![],
+            fill_color: None,
         }
     }
 }
 
 /// A Much more customiseable and advanced version of [`SimpleBar`].
-/// 
+///
 /// To construct, use [`Builder`]
-/// 
+///
 /// [`Builder`]: Builder
 /// [`SimpleBar`]: super::simple/*lmao*/::SimpleBar
 #[derive(Clone, Debug, Hash)]
 pub struct CustomBar {
-    job_name: String,
-    progress: usize,
-    max_hint: usize,
-    finished: bool,
-    last_iter: Instant,
+    job: Job,
     elems: BarElements,
+    units: Units,
+    styles: BarStyles,
 }
 
 impl CustomBar {
     pub fn set_name(&mut self, job_name: String) {
-        self.job_name = job_name;
+        self.job.name = job_name;
     }
 }
 
 impl crate::IsBar for CustomBar {
     fn done(&mut self) {
-        self.finished = true;
+        self.job.done();
     }
 
-    #[must_use]
     fn is_done(&self) -> bool {
-        self.finished
+        self.job.is_done()
     }
 
-    #[must_use]
     fn display(&mut self) -> String {
-        let now = Instant::now();
-        let time_fmt = format!("{:.3}", 1f32 / (now - self.last_iter).as_secs_f32());
-        self.last_iter = now;
-
-        //TODO make this not use default
-        let width = crate::utils::term_width().unwrap_or(81) as usize;
-
-        let mut res = String::with_capacity(width);
-
-        let percentage = self.progress * 100 / self.max_hint;
-        let bar_len = width.checked_sub(
-            self.job_name.len() +
-            1 /* gap */ +
-            1 /* bar start */ +
-            /* bar would go here */
-            1 /* bar end */ +
-            1 /* gap */ +
-            5 /* precent len */ +
-            1 /*gap*/ +
-            time_fmt.len() /* time amnt*/+
-            1 /*gap*/ +
-            self.elems.unit.len() + 2 /* unit len (___/s) */
-        ).unwrap();
-        let bar_finished_len = (bar_len as f32 * percentage as f32 / 100.0) as isize;
+        self.display_with(&crate::RenderContext::detect())
+    }
 
-        res += "\r";
-        res += &self.job_name;
-        res += " ";
-        res += &self.elems.start;
-        for _ in 0..bar_finished_len {
-            res += &self.elems.filled;
+    fn display_with(&mut self, ctx: &crate::RenderContext) -> String {
+        self.job.sync();
+        let percentage = self.job.state.percentage();
+        let known = self.job.state.len().is_some();
+        let mut styles = self.styles;
+        if let Some(color) = self
+            .elems
+            .fill_color
+            .as_ref()
+            .and_then(|fill| fill.color_at(self.job.state.fraction().unwrap_or(0.0)))
+        {
+            styles.filled = styles.filled.fg(color);
         }
-        for _ in bar_finished_len as usize..bar_len {
-            res += &self.elems.empty;
+        let styles = styles.for_context(ctx);
+        if let Some(line) = self.job.final_line(&styles, ctx) {
+            return format!("\r{line}");
+        }
+        let pos = self.units.format(self.job.state.pos() as u64);
+        let rate = match self.units {
+            Units::Plain => format!(" {:.3} {}/s", self.job.state.rate(), self.elems.unit),
+            // the unit is already part of the rate
+            units if units.is_bytes() => format!(" {}/s", units.format_rate(self.job.state.rate())),
+            units => format!(
+                " {} {}/s",
+                units.format_rate(self.job.state.rate()),
+                self.elems.unit
+            ),
+        };
+        let mut layout = Layout(vec![
+            Part::Name {
+                text: self.job.name.clone(),
+                width: None,
+                align: Align::Left,
+                style: styles.name,
+            },
+            Part::Text(format!(" {}", styles.brackets.paint(&self.elems.start))),
+            Part::Bar(None),
+            Part::Text(styles.brackets.paint(&self.elems.end)),
+            // without a length, show how much has been done instead
+            if known {
+                //pad to 4 chars on left
+                Part::Text(pad(
+                    &styles.percentage.paint(&format!("{}%", percentage)),
+                    5,
+                    Align::Right,
+                ))
+            } else {
+                Part::Text(format!(" {}", styles.percentage.paint(&pos)))
+            },
+            Part::Optional {
+                text: rate,
+                priority: 1,
+            },
+            Part::Optional {
+                text: if known {
+                    format!(
+                        " {} eta {}",
+                        HumanDuration(self.job.state.elapsed()),
+                        format::eta(self.job.state.eta())
+                    )
+                } else {
+                    format!(" {}", HumanDuration(self.job.state.elapsed()))
+                },
+                priority: 0,
+            },
+        ]);
+
+        // with units, how much has been done is worth showing as well as the percentage
+        if let (Some(len), false) = (self.job.state.len(), self.units == Units::Plain) {
+            layout.0.insert(
+                5,
+                Part::Optional {
+                    text: format!(" ({} / {})", pos, self.units.format(len as u64)),
+                    priority: 2,
+                },
+            );
         }
-        res += &self.elems.end;
-        //pad to 4 chars on left
-        res += &format!("{:>4}%", percentage);
-        res += " ";
-        res += &time_fmt;
-        res += " ";
-        res += &self.elems.unit;
-        res += "/s";
 
+        let mut res = String::with_capacity(ctx.width);
+        res += "\r";
+        res += &layout.render(ctx.width, |bar_len| {
+            if !known {
+                return indeterminate(
+                    self.job.state.elapsed(),
+                    bar_len,
+                    &self.elems.filled,
+                    &self.elems.empty,
+                    &styles,
+                );
+            }
+            fill(
+                bar_len,
+                self.job.state.fraction().unwrap_or(0.0),
+                &self.elems.filled,
+                &self.elems.empty,
+                &self.elems.partials,
+                &styles,
+            )
+        });
         res
     }
 
-    #[must_use]
+    fn display_plain(&mut self) -> String {
+        self.job.sync();
+        if let Some(line) = self.job.final_plain() {
+            return line;
+        }
+        match self.job.state.len() {
+            Some(len) => format!(
+                "{}: {}% ({}/{})",
+                self.job.name,
+                self.job.state.percentage(),
+                self.units.format(self.job.state.pos() as u64),
+                self.units.format(len as u64)
+            ),
+            None => format!(
+                "{}: {}",
+                self.job.name,
+                self.units.format(self.job.state.pos() as u64)
+            ),
+        }
+    }
+
     fn close_method(&self) -> crate::isbar::BarCloseMethod {
         crate::isbar::BarCloseMethod::LeaveBehind
     }
+
+    fn finish_state(&self) -> FinishState {
+        self.job.finish_state()
+    }
+
+    fn fraction(&self) -> Option<f64> {
+        self.job.state.fraction()
+    }
+
+    fn sync(&mut self) {
+        self.job.sync();
+    }
 }
 
 impl crate::subsets::IteratorProgress for CustomBar {
     fn set_progress(&mut self, progress: usize) {
-        self.progress = progress;
+        self.job.state.set_pos(progress);
     }
 
     fn set_size_hint(&mut self, hint: usize) {
-        self.max_hint = hint;
+        self.job.state.set_len(hint);
     }
 }
 
 /// Builder pattern builder for [`CustomBar`]
-/// 
+///
 /// to create the builder, use [`new`] and to construct the bar use [`build`]
-/// 
+///
 /// [`CustomBar`]: CustomBar
 /// [`new`]: Builder::new
 /// [`build`]: Builder::build
@@ -138,6 +270,9 @@ pub struct Builder {
     job_name: String,
     hint: usize,
     elems: BarElements,
+    rate_window: Duration,
+    units: Units,
+    styles: BarStyles,
 }
 
 impl Builder {
@@ -150,10 +285,15 @@ impl Builder {
             elems: BarElements {
                 ..Default::default()
             },
+            rate_window: crate::state::DEFAULT_RATE_WINDOW,
+            units: Units::Plain,
+            styles: BarStyles::default(),
         }
     }
 
-    /// Sets the size hint
+    /// Sets the size hint. if this is 0, the length is unknown until it is given a hint with [`set_size_hint`]
+    ///
+    /// [`set_size_hint`]: crate::subsets::IteratorProgress::set_size_hint
     #[must_use]
     pub fn hint(mut self, hint: usize) -> Self {
         self.hint = hint;
@@ -167,20 +307,39 @@ impl Builder {
         self
     }
 
-    /// Set the string for the filled bar section (should be one charecter in length, is repeated)
+    /// Set the string for the filled bar section (is repeated, and can be more than one column wide)
     #[must_use]
     pub fn filled(mut self, v: impl ToString) -> Self {
         self.elems.filled = v.to_string();
         self
     }
 
-    /// Set the string for the empty bar section (should be one charecter in length, is repeated)
+    /// Set the string for the empty bar section (is repeated, and can be more than one column wide)
     #[must_use]
     pub fn empty(mut self, v: impl ToString) -> Self {
         self.elems.empty = v.to_string();
         self
     }
 
+    /// Set the glyphs drawn at the edge of the filled bar section, to show how full that cell is.
+    ///
+    /// these go from least to most filled, with the last one being a full cell (like `▏▎▍▌▋▊▉█`),
+    /// and should be as wide as the filled string. by default there are none, so the bar fills one cell at a time
+    #[must_use]
+    pub fn partials(mut self, partials: impl IntoIterator<Item = impl ToString>) -> Self {
+        self.elems.partials = partials.into_iter().map(|p| p.to_string()).collect();
+        self
+    }
+
+    /// Set a color for the filled bar section that changes as the bar fills up, overriding the color of the filled style
+    ///
+    /// see [`FillColor`] for an example
+    #[must_use]
+    pub fn fill_color(mut self, color: FillColor) -> Self {
+        self.elems.fill_color = Some(color);
+        self
+    }
+
     /// Set the start string for the bar section
     #[must_use]
     pub fn start(mut self, v: impl ToString) -> Self {
@@ -195,6 +354,30 @@ impl Builder {
         self
     }
 
+    /// Sets how the progress and rate are formatted, like [`Units::BinaryBytes`] for file transfers.
+    ///
+    /// with units other than [`Units::Plain`], how much has been done is shown as well as the percentage
+    ///
+    /// ```rust
+    /// use stati::bars::custom::Builder;
+    /// use stati::format::Units;
+    /// use stati::prelude::*;
+    ///
+    /// # fn main() {
+    /// let mut bar = Builder::new("Download")
+    ///     .units(Units::BinaryBytes)
+    ///     .hint(1_288_490_189)
+    ///     .build();
+    /// bar.set_progress(12_897_484);
+    /// assert_eq!(bar.display_plain(), "Download: 1% (12.3 MiB/1.2 GiB)");
+    /// # }
+    /// ```
+    #[must_use]
+    pub const fn units(mut self, units: Units) -> Self {
+        self.units = units;
+        self
+    }
+
     /// Sets the unit in ___/sec for the progress bar
     #[must_use]
     pub fn unit(mut self, v: impl ToString) -> Self {
@@ -202,16 +385,40 @@ impl Builder {
         self
     }
 
+    /// Sets how far back the ___/sec rate looks, see [`ProgressState::set_window`]
+    #[must_use]
+    pub const fn rate_window(mut self, window: Duration) -> Self {
+        self.rate_window = window;
+        self
+    }
+
+    /// Sets the colors and styles of the bar (see [`style`](crate::style))
+    #[must_use]
+    pub const fn styles(mut self, styles: BarStyles) -> Self {
+        self.styles = styles;
+        self
+    }
+
     /// Build the [`CustomBar`]
     #[must_use]
     pub fn build(self) -> CustomBar {
+        let mut state = ProgressState::new(self.hint);
+        state.set_window(self.rate_window);
         CustomBar {
-            job_name: self.job_name,
-            max_hint: self.hint,
+            job: Job::new(self.job_name, state),
             elems: self.elems,
-            progress: 0,
-            last_iter: Instant::now(),
-            finished: false,
+            units: self.units,
+            styles: self.styles,
         }
     }
 }
+
+impl super::job::HasJob for CustomBar {
+    fn job(&self) -> &Job {
+        &self.job
+    }
+
+    fn job_mut(&mut self) -> &mut Job {
+        &mut self.job
+    }
+}
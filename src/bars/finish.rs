@@ -0,0 +1,95 @@
+//! The final line drawn by the bundled bars, once they have succeeded, failed or been abandoned
+
+use std::time::Duration;
+
+use super::layout::{Align, Layout, Part};
+use crate::format::HumanDuration;
+use crate::style::{BarStyles, Color, Style};
+use crate::{FinishState, ProgressState, RenderContext};
+
+/// How a bundled bar was finished
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub(crate) struct Finish {
+    pub(crate) state: FinishState,
+    pub(crate) message: String,
+}
+
+impl Finish {
+    /// Finished with [`done`](crate::IsBar::done), so the bar is drawn as normal
+    pub(crate) const fn done() -> Self {
+        Self {
+            state: FinishState::Done,
+            message: String::new(),
+        }
+    }
+
+    /// Abandoned, with a message saying how far along the job got.
+    /// jobs that never got anywhere, like spinners, are only said to be abandoned
+    pub(crate) fn abandoned(state: &ProgressState) -> Self {
+        Self {
+            state: FinishState::Abandoned,
+            message: match (state.len(), state.pos()) {
+                (Some(_), _) => format!("abandoned at {}%", state.percentage()),
+                (None, 0) => "abandoned".into(),
+                (None, pos) => format!("abandoned at {pos}"),
+            },
+        }
+    }
+
+    /// The symbol the final line starts with, and its color
+    const fn symbol(&self) -> Option<(&'static str, Color)> {
+        match self.state {
+            FinishState::Done => None,
+            FinishState::Success => Some(("✔", Color::Green)),
+            FinishState::Error => Some(("✘", Color::Red)),
+            FinishState::Abandoned => Some(("⊘", Color::Yellow)),
+        }
+    }
+
+    /// The final line, like `✔ name: message (1m23s)`.
+    /// `None` if the bar was only [done](Self::done), and should be drawn as normal
+    pub(crate) fn display_with(
+        &self,
+        name: &str,
+        elapsed: Duration,
+        styles: &BarStyles,
+        ctx: &RenderContext,
+    ) -> Option<String> {
+        let (symbol, color) = self.symbol()?;
+        let symbol_style = if ctx.color {
+            Style::new().fg(color)
+        } else {
+            Style::new()
+        };
+        let mut parts = vec![
+            Part::Text(symbol_style.paint(symbol) + " "),
+            Part::Name {
+                text: name.into(),
+                width: None,
+                align: Align::Left,
+                style: styles.name,
+            },
+        ];
+        if !self.message.is_empty() {
+            parts.push(Part::Text(format!(
+                ": {}",
+                styles.message.paint(&self.message)
+            )));
+        }
+        parts.push(Part::Optional {
+            text: format!(" ({})", HumanDuration(elapsed)),
+            priority: 0,
+        });
+        Some(Layout(parts).render(ctx.width, |_| String::new()))
+    }
+
+    /// Like [`display_with`](Self::display_with), but for [`display_plain`](crate::IsBar::display_plain)
+    pub(crate) fn display_plain(&self, name: &str, elapsed: Duration) -> Option<String> {
+        let (symbol, _) = self.symbol()?;
+        let mut res = format!("{symbol} {name}");
+        if !self.message.is_empty() {
+            res += &format!(": {}", self.message);
+        }
+        Some(res + &format!(" ({})", HumanDuration(elapsed)))
+    }
+}
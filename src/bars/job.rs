@@ -0,0 +1,144 @@
+//! What the bundled bars have in common: a named job, how far along it is, and how it was finished
+
+use super::finish::Finish;
+use crate::style::BarStyles;
+use crate::subsets::CounterProgress;
+use crate::{FinishState, ProgressCounter, ProgressState, RenderContext};
+
+/// Methods shared by all the bars in [`bars`](super), for finishing them and checking on their progress
+///
+/// this is sealed, so it can only be implemented by the bars in this crate
+pub trait BundledBar: HasJob {
+    /// The progress of the bar, including elapsed time and ETA
+    #[must_use]
+    fn state(&self) -> &ProgressState {
+        &self.job().state
+    }
+
+    /// How many items are being done per second, see [`ProgressState::rate`]
+    #[must_use]
+    fn rate(&self) -> f64 {
+        self.job().state.rate()
+    }
+
+    /// Finishes the bar successfully. it is drawn one last time with a check mark, `message`,
+    /// and how long it took (see [finishing](super#finishing))
+    fn finish_with_success(&mut self, message: impl ToString) {
+        self.job_mut()
+            .finish(FinishState::Success, message.to_string());
+    }
+
+    /// Finishes the bar with an error. it is drawn one last time with a cross, `message` and how long it took
+    fn finish_with_error(&mut self, message: impl ToString) {
+        self.job_mut()
+            .finish(FinishState::Error, message.to_string());
+    }
+
+    /// Finishes the bar without completing it. it is drawn one last time saying how far along it got
+    fn abandon(&mut self) {
+        self.job_mut().abandon();
+    }
+}
+
+impl<T: HasJob> BundledBar for T {}
+
+/// Messages set through the counter replace the bar's name, unless it has a message of its own
+/// (like `{msg}` for [`TemplateBar`](super::TemplateBar))
+impl<T: HasJob> CounterProgress for T {
+    fn counter(&mut self) -> ProgressCounter {
+        self.job_mut().counter()
+    }
+}
+
+/// Gives [`BundledBar`] the bar's [`Job`]. this is not reachable from outside the crate, which seals [`BundledBar`]
+pub trait HasJob {
+    fn job(&self) -> &Job;
+
+    fn job_mut(&mut self) -> &mut Job;
+}
+
+/// The job a bundled bar is showing
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Job {
+    pub(crate) name: String,
+    /// shown next to the name, for bars that have somewhere to put it.
+    /// messages set through the [`counter`](Self::counter) go here, or replace the name if this is `None`
+    pub(crate) message: Option<String>,
+    pub(crate) state: ProgressState,
+    /// `None` until the bar is done
+    pub(crate) finish: Option<Finish>,
+}
+
+impl Job {
+    pub(crate) const fn new(name: String, state: ProgressState) -> Self {
+        Self {
+            name,
+            message: None,
+            state,
+            finish: None,
+        }
+    }
+
+    /// Gives the job a message, so messages from the [`counter`](Self::counter) no longer replace the name
+    pub(crate) fn with_message(mut self, message: String) -> Self {
+        self.message = Some(message);
+        self
+    }
+
+    /// A handle for updating the job from any thread, see [`ProgressState::counter`]
+    pub(crate) fn counter(&mut self) -> ProgressCounter {
+        self.state.counter()
+    }
+
+    /// Applies changes made through the [`counter`](Self::counter). called before the job is drawn or finished
+    pub(crate) fn sync(&mut self) {
+        if let Some(message) = self.state.sync() {
+            *self.message.as_mut().unwrap_or(&mut self.name) = message;
+        }
+    }
+
+    /// Finishes the job, replacing how it was finished before, and stops the clock
+    pub(crate) fn finish(&mut self, state: FinishState, message: String) {
+        self.sync();
+        self.finish = Some(Finish { state, message });
+        self.state.stop();
+    }
+
+    /// Finishes the job with a message saying how far along it got
+    pub(crate) fn abandon(&mut self) {
+        self.sync();
+        let finish = Finish::abandoned(&self.state);
+        self.finish(finish.state, finish.message);
+    }
+
+    /// For [`IsBar::done`](crate::IsBar::done). a job that was already finished keeps how it was finished
+    pub(crate) fn done(&mut self) {
+        self.sync();
+        self.finish.get_or_insert(Finish::done());
+        self.state.stop();
+    }
+
+    pub(crate) const fn is_done(&self) -> bool {
+        self.finish.is_some()
+    }
+
+    pub(crate) fn finish_state(&self) -> FinishState {
+        self.finish
+            .as_ref()
+            .map_or(FinishState::Done, |finish| finish.state)
+    }
+
+    /// The final line to draw instead of the bar, see [`Finish::display_with`]
+    pub(crate) fn final_line(&self, styles: &BarStyles, ctx: &RenderContext) -> Option<String> {
+        self.finish
+            .as_ref()
+            .and_then(|finish| finish.display_with(&self.name, self.state.elapsed(), styles, ctx))
+    }
+
+    /// Like [`final_line`](Self::final_line), but for [`display_plain`](crate::IsBar::display_plain)
+    pub(crate) fn final_plain(&self) -> Option<String> {
+        self.finish
+            .as_ref()
+            .and_then(|finish| finish.display_plain(&self.name, self.state.elapsed()))
+    }
+}
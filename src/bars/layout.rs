@@ -0,0 +1,451 @@
+//! Layout shared by the bundled bars, fitting a line into the width of the terminal.
+//!
+//! when there is not enough space for everything, a line is shrunk in steps:
+//! 1. padding around the name is removed, and the bar is shrunk down to [`MIN_BAR`] columns
+//! 2. the name is truncated, ending with [`ELLIPSIS`]
+//! 3. optional parts are dropped, and the steps above are tried again.
+//!    the spaces that separated a dropped part from the rest of the line are dropped with it
+//! 4. if it still does not fit, bars and names are removed completely, and the rest is cut off
+
+/// Put at the end of names that have been truncated
+pub(crate) const ELLIPSIS: &str = "…";
+
+/// Bars are not shrunk below this many columns, until there is no other option
+pub(crate) const MIN_BAR: usize = 5;
+
+/// How a value is aligned when it is padded
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub(crate) enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+use std::time::Duration;
+
+use crate::style::{BarStyles, Style};
+use crate::utils::{display_width as text_width, truncate_to_width};
+
+/// Pads `value` to `width` columns
+pub(crate) fn pad(value: &str, width: usize, align: Align) -> String {
+    let padding = width.saturating_sub(text_width(value));
+    let (left, right) = match align {
+        Align::Left => (0, padding),
+        Align::Right => (padding, 0),
+        Align::Center => (padding / 2, padding - padding / 2),
+    };
+    " ".repeat(left) + value + &" ".repeat(right)
+}
+
+/// Repeats `elem` to fill exactly `width` columns.
+/// if `elem` is wider than one column and does not fit evenly, the rest is filled with spaces
+pub(crate) fn repeat_to_width(elem: &str, width: usize) -> String {
+    let elem_width = text_width(elem);
+    if elem_width == 0 {
+        return " ".repeat(width);
+    }
+    elem.repeat(width / elem_width) + &" ".repeat(width % elem_width)
+}
+
+/// Draws a bar `width` columns wide, `fraction` of the way full.
+///
+/// `partials` are drawn in the cell at the edge of the filled section, to show how full that cell is.
+/// they are ordered from least to most filled, with the last one being a full cell.
+/// the partial cell is styled like the filled section
+pub(crate) fn fill(
+    width: usize,
+    fraction: f64,
+    filled: &str,
+    empty: &str,
+    partials: &[String],
+    styles: &BarStyles,
+) -> String {
+    let cell = text_width(filled).max(1);
+    let cells = width / cell;
+    let exact = cells as f64 * fraction.clamp(0.0, 1.0);
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let full = exact as usize;
+    // a full bar covers the columns left over after the last whole cell too, so it never ends in an empty one
+    let filled_width = if fraction >= 1.0 { width } else { full * cell };
+    let mut res = repeat_to_width(filled, filled_width);
+    let mut used = filled_width;
+    if full < cells && !partials.is_empty() {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let partial = ((exact - full as f64) * partials.len() as f64) as usize;
+        // a cell less than one step full stays empty
+        if let Some(glyph) = partial.checked_sub(1).map(|i| &partials[i]) {
+            res += glyph;
+            used += text_width(glyph);
+        }
+    }
+    styles.filled.paint(&res)
+        + &styles
+            .empty
+            .paint(&repeat_to_width(empty, width.saturating_sub(used)))
+}
+
+/// Draws a bar of unknown length, `width` columns wide:
+/// a short filled section that bounces back and forth, moving one column every 100ms
+pub(crate) fn indeterminate(
+    elapsed: Duration,
+    width: usize,
+    filled: &str,
+    empty: &str,
+    styles: &BarStyles,
+) -> String {
+    let section = (width / 4).clamp(1, width.max(1)).min(width);
+    let travel = width - section;
+    let offset = if travel == 0 {
+        0
+    } else {
+        let step = (elapsed.as_millis() / 100 % (2 * travel) as u128) as usize;
+        // go back once the end has been reached
+        if step <= travel {
+            step
+        } else {
+            2 * travel - step
+        }
+    };
+    styles.empty.paint(&repeat_to_width(empty, offset))
+        + &styles.filled.paint(&repeat_to_width(filled, section))
+        + &styles.empty.paint(&repeat_to_width(empty, travel - offset))
+}
+
+/// One piece of a line
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub(crate) enum Part {
+    /// Always shown
+    Text(String),
+    /// The name of the job, padded to `width` if there is space. `style` is applied after it is truncated
+    Name {
+        text: String,
+        width: Option<usize>,
+        align: Align,
+        style: Style,
+    },
+    /// The bar itself, filling the space that is left. if it has a size, it will not grow past it
+    Bar(Option<usize>),
+    /// Dropped if there is not enough space. lower priorities are dropped first, then ones further right
+    Optional { text: String, priority: u8 },
+}
+
+/// A line made of [`Part`]s, that can be fit into any width
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub(crate) struct Layout(pub(crate) Vec<Part>);
+
+impl Layout {
+    /// Lays out the line to fit in `width` columns, leaving the last one empty so the cursor does not wrap.
+    /// `bar` is called to draw each bar, and must return exactly as many columns as it is given
+    pub(crate) fn render(&self, width: usize, mut bar: impl FnMut(usize) -> String) -> String {
+        let avail = width.saturating_sub(1);
+        let mut optional = self
+            .0
+            .iter()
+            .enumerate()
+            .filter_map(|(i, part)| match part {
+                Part::Optional { priority, .. } => Some((*priority, i)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        optional.sort_by_key(|&(priority, i)| (priority, std::cmp::Reverse(i)));
+        for dropped in 0..=optional.len() {
+            let shown = |i: usize| !optional[..dropped].iter().any(|&(_, o)| o == i);
+            if let Some(sizes) = self.fit(avail, shown, false) {
+                return self.draw(&sizes, shown, &mut bar);
+            }
+        }
+        let sizes = self
+            .fit(avail, |_| false, true)
+            .expect("squeezed layouts always fit");
+        truncate_to_width(&self.draw(&sizes, |_| false, &mut bar), avail).into()
+    }
+
+    /// The text of the [`Part::Text`] at `index`, without the spaces that separated it from a dropped optional part.
+    ///
+    /// a dropped part takes the spaces before it, or the ones after it if there is nothing before it
+    fn text(&self, index: usize, shown: impl Fn(usize) -> bool) -> &str {
+        let Part::Text(text) = &self.0[index] else {
+            unreachable!("only text parts have separators");
+        };
+        let dropped = |i: usize| matches!(self.0.get(i), Some(Part::Optional { .. })) && !shown(i);
+        let mut text = text.as_str();
+        if dropped(index + 1) {
+            text = text.trim_end();
+        }
+        // nothing but dropped parts before it
+        if index > 0 && (0..index).all(dropped) {
+            text = text.trim_start();
+        }
+        text
+    }
+
+    /// Works out how many columns each part gets, or `None` if it does not fit.
+    /// when `squeeze` is set, names and bars can shrink to nothing, and the result is always `Some`
+    fn fit(
+        &self,
+        avail: usize,
+        shown: impl Fn(usize) -> bool,
+        squeeze: bool,
+    ) -> Option<Vec<usize>> {
+        let mut sizes = vec![0; self.0.len()];
+        let (mut fixed, mut bar_min, mut names) = (0, 0, 0);
+        for (i, part) in self.0.iter().enumerate() {
+            match part {
+                Part::Text(_) => sizes[i] = text_width(self.text(i, &shown)),
+                Part::Optional { text, .. } if shown(i) => sizes[i] = text_width(text),
+                Part::Optional { .. } => {}
+                Part::Bar(max) if !squeeze => {
+                    sizes[i] = max.map_or(MIN_BAR, |max| max.min(MIN_BAR))
+                }
+                Part::Bar(_) => {}
+                Part::Name { text, .. } => {
+                    sizes[i] = text_width(text);
+                    names += sizes[i];
+                    continue;
+                }
+            }
+            match part {
+                Part::Bar(_) => bar_min += sizes[i],
+                _ => fixed += sizes[i],
+            }
+        }
+        let space = if squeeze {
+            avail.saturating_sub(fixed)
+        } else {
+            avail.checked_sub(fixed + bar_min)?
+        };
+        if space >= names {
+            let mut left = space - names;
+            // padding comes first, as it keeps bars lined up
+            for (i, part) in self.0.iter().enumerate() {
+                if let Part::Name {
+                    width: Some(width), ..
+                } = part
+                {
+                    let extra = width.saturating_sub(sizes[i]).min(left);
+                    sizes[i] += extra;
+                    left -= extra;
+                }
+            }
+            self.grow_bars(&mut sizes, left);
+        } else {
+            // truncate names from the right, keeping at least one character and the ellipsis
+            let mut over = names - space;
+            for (i, part) in self.0.iter().enumerate().rev() {
+                if let Part::Name { .. } = part {
+                    let min = if squeeze {
+                        0
+                    } else {
+                        sizes[i].min(1 + text_width(ELLIPSIS))
+                    };
+                    let cut = (sizes[i] - min).min(over);
+                    sizes[i] -= cut;
+                    over -= cut;
+                }
+            }
+            if over > 0 {
+                return None;
+            }
+        }
+        Some(sizes)
+    }
+
+    /// Shares `left` columns between the bars, up to their maximum size
+    fn grow_bars(&self, sizes: &mut [usize], mut left: usize) {
+        loop {
+            let growable = self
+                .0
+                .iter()
+                .enumerate()
+                .filter(|(i, part)| matches!(part, Part::Bar(max) if max.is_none_or(|max| sizes[*i] < max)))
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>();
+            if growable.is_empty() || left == 0 {
+                return;
+            }
+            let share = (left / growable.len()).max(1);
+            for i in growable {
+                let Part::Bar(max) = self.0[i] else {
+                    unreachable!()
+                };
+                let grow = share
+                    .min(left)
+                    .min(max.map_or(usize::MAX, |max| max - sizes[i]));
+                sizes[i] += grow;
+                left -= grow;
+            }
+        }
+    }
+
+    fn draw(
+        &self,
+        sizes: &[usize],
+        shown: impl Fn(usize) -> bool,
+        bar: &mut impl FnMut(usize) -> String,
+    ) -> String {
+        let mut res = String::new();
+        for (i, (part, &size)) in self.0.iter().zip(sizes).enumerate() {
+            match part {
+                Part::Text(_) => res += self.text(i, &shown),
+                Part::Optional { text, .. } if size > 0 => res += text,
+                Part::Optional { .. } => {}
+                Part::Bar(_) => res += &bar(size),
+                Part::Name {
+                    text, align, style, ..
+                } => res += &truncate(text, size, *align, style),
+            }
+        }
+        res
+    }
+}
+
+/// Fits `text` into exactly `width` columns, padding it or truncating it with an ellipsis.
+/// the padding is not styled
+fn truncate(text: &str, width: usize, align: Align, style: &Style) -> String {
+    if text_width(text) <= width {
+        return pad(&style.paint(text), width, align);
+    }
+    let Some(keep) = width.checked_sub(text_width(ELLIPSIS)) else {
+        return " ".repeat(width);
+    };
+    let kept = truncate_to_width(text, keep);
+    // a wide character may not fit exactly, so pad out the gap it leaves
+    style.paint(&kept) + &" ".repeat(keep - text_width(&kept)) + &style.paint(ELLIPSIS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fill, Align, Layout, Part, ELLIPSIS, MIN_BAR};
+    use crate::style::{BarStyles, Style};
+    use crate::utils::display_width;
+
+    const NAME: &str = "downloading";
+
+    fn layout() -> Layout {
+        Layout(vec![
+            Part::Name {
+                text: NAME.into(),
+                width: Some(14),
+                align: Align::Left,
+                style: Style::new(),
+            },
+            Part::Text(" [".into()),
+            Part::Bar(None),
+            Part::Text("] ".into()),
+            Part::Text("50%".into()),
+            Part::Text(" ".into()),
+            Part::Optional {
+                text: "RATE".into(),
+                priority: 1,
+            },
+            Part::Text(" ".into()),
+            Part::Optional {
+                text: "ETA".into(),
+                priority: 0,
+            },
+        ])
+    }
+
+    #[test]
+    fn shrinks_in_order() {
+        let layout = layout();
+        for width in 0..=120 {
+            let line = layout.render(width, |len| "#".repeat(len));
+            let avail = width.saturating_sub(1);
+            assert!(
+                display_width(&line) <= avail,
+                "{width}: {line:?} is too wide"
+            );
+            let bar = line.matches('#').count();
+            if bar < MIN_BAR {
+                // squeezed, so everything optional is gone and the rest is cut off
+                assert!(
+                    !line.contains("RATE") && !line.contains("ETA"),
+                    "{width}: {line:?}"
+                );
+                continue;
+            }
+            assert_eq!(
+                display_width(&line),
+                avail,
+                "{width}: {line:?} does not fill the line"
+            );
+            let name = line.split(" [").next().unwrap();
+            let (rate, eta) = (line.contains("RATE"), line.contains("ETA"));
+            // the lower priority part goes first
+            assert!(rate || !eta, "{width}: {line:?}");
+            if !rate || !eta {
+                // parts are only dropped once the name and bar can not shrink enough to keep the last one dropped
+                let last = if rate { " ETA" } else { " RATE" };
+                let smallest = avail - (display_width(name) - 2) - (bar - MIN_BAR);
+                assert!(smallest + last.len() > avail, "{width}: {line:?}");
+                // along with the spaces that separated them
+                assert!(!line.ends_with(' '), "{width}: {line:?}");
+            }
+            if name.contains(ELLIPSIS) {
+                assert_eq!(bar, MIN_BAR, "{width}: {line:?}");
+            }
+            if bar > MIN_BAR {
+                // the name is padded before the bar grows
+                assert_eq!(name, format!("{NAME:<14}"), "{width}: {line:?}");
+                assert!(rate && eta, "{width}: {line:?}");
+            }
+            assert!(!line[name.len()..].contains("  "), "{width}: {line:?}");
+        }
+    }
+
+    #[test]
+    fn dropped_parts_take_their_separators() {
+        let layout = Layout(vec![
+            Part::Name {
+                text: "Job".into(),
+                width: None,
+                align: Align::Left,
+                style: Style::new(),
+            },
+            Part::Text(" [".into()),
+            Part::Bar(None),
+            Part::Text("] 50% 50/100".into()),
+            Part::Text(" ".into()),
+            Part::Optional {
+                text: "1.0/s".into(),
+                priority: 0,
+            },
+            Part::Text(" ".into()),
+            Part::Optional {
+                text: "5s".into(),
+                priority: 0,
+            },
+        ]);
+        let bar = |len| "=".repeat(len);
+        assert_eq!(
+            layout.render(40, bar),
+            "Job [=============] 50% 50/100 1.0/s 5s"
+        );
+        assert_eq!(layout.render(32, bar), "Job [=====] 50% 50/100 1.0/s 5s");
+        assert_eq!(layout.render(31, bar), "J… [=====] 50% 50/100 1.0/s 5s");
+        // the one further right goes first
+        assert_eq!(layout.render(30, bar), "Job [======] 50% 50/100 1.0/s");
+        assert_eq!(layout.render(27, bar), "Job [=========] 50% 50/100");
+        assert_eq!(layout.render(22, bar), "J… [=====] 50% 50/100");
+
+        // a dropped part at the start takes the spaces after it
+        let layout = Layout(vec![
+            Part::Optional {
+                text: "spinner".into(),
+                priority: 0,
+            },
+            Part::Text(" done".into()),
+        ]);
+        assert_eq!(layout.render(6, bar), "done");
+    }
+
+    #[test]
+    fn full_bar_with_wide_glyphs_has_no_empty_cell() {
+        let styles = BarStyles::default();
+        // 5 columns only fit two 2-column cells, the last column is padding
+        assert_eq!(fill(5, 1.0, "██", "-", &[], &styles), "████ ");
+        assert_eq!(fill(5, 0.5, "██", "-", &[], &styles), "██---");
+        assert_eq!(fill(6, 1.0, "██", "-", &[], &styles), "██████");
+    }
+}
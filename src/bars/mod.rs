@@ -1,5 +1,18 @@
 //! Simple progress bar implementations.
 //! use these or create your own!
+//!
+//! # Finishing
+//! apart from [`done`](crate::IsBar::done), the bars here can be finished with
+//! [`finish_with_success`](BundledBar::finish_with_success), [`finish_with_error`](BundledBar::finish_with_error)
+//! or [`abandon`](BundledBar::abandon).
+//! they are then drawn one last time as a single line, like `✔ name: message (1m23s)`,
+//! starting with a check mark, a cross or `⊘`, and ending with how long the job took.
+//! the manager counts how many bars ended each way (see [`FinishState`](crate::FinishState))
+
+mod finish;
+mod job;
+pub use job::BundledBar;
+mod layout;
 
 pub mod simple;
 pub use simple::SimpleBar;
@@ -9,3 +22,6 @@ pub use spinny::SpinniBuilder;
 
 pub mod custom;
 pub use custom::CustomBar;
+
+pub mod template;
+pub use template::TemplateBar;
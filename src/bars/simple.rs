@@ -4,45 +4,72 @@ const START: &str = "[";
 const END: &str = "]";
 const UNIT: &str = "%";
 
+use super::job::Job;
+use super::layout::{fill, indeterminate, pad, Align, Layout, Part};
+use crate::format::{self, HumanDuration};
+use crate::style::BarStyles;
+use crate::{FinishState, ProgressState};
+
 /// A simple progress bar implementation, based off that of
 /// the progress crates progresbar
 #[derive(Clone, Debug, Hash)]
 pub struct SimpleBar {
-    job_name: String,
-    progress: usize,
-    max_hint: usize,
-    finished: bool,
+    job: Job,
+    styles: BarStyles,
 }
 
 impl SimpleBar {
     /// name: the name of the job
     ///
-    /// hint: hint for the maximum value this will reach
+    /// hint: hint for the maximum value this will reach.
+    /// if this is 0, the length is unknown, and the bar shows how much has been done instead of a percentage
+    /// until it is given a hint with [`set_size_hint`]
+    ///
+    ///
+    /// ```rust
+    /// use stati::bars::SimpleBar;
+    /// use stati::prelude::*;
+    ///
+    /// # fn main() {
+    /// let mut bar = SimpleBar::new("Reading", 0);
+    /// bar.set_progress(42);
+    /// assert_eq!(bar.display_plain(), "Reading: 42");
+    /// bar.set_size_hint(84);
+    /// assert_eq!(bar.display_plain(), "Reading: 50% (42/84)");
+    /// # }
+    /// ```
+    ///
+    /// [`set_size_hint`]: crate::subsets::IteratorProgress::set_size_hint
     pub fn new(name: impl ToString, hint: usize) -> Self {
         Self {
-            job_name: name
-                .to_string()
-                .chars()
-                .filter(|ch| ch != &'\n' || ch != &'\r')
-                .collect(),
-            progress: 0,
-            max_hint: hint,
-            finished: false,
+            job: Job::new(
+                name.to_string()
+                    .chars()
+                    .filter(|ch| ch != &'\n' || ch != &'\r')
+                    .collect(),
+                ProgressState::new(hint),
+            ),
+            styles: BarStyles::default(),
         }
     }
 
     pub fn set_name(&mut self, job_name: String) {
-        self.job_name = job_name;
+        self.job.name = job_name;
+    }
+
+    /// Sets the colors and styles of the bar (see [`style`](crate::style))
+    pub fn set_styles(&mut self, styles: BarStyles) {
+        self.styles = styles;
     }
 }
 
 impl crate::IsBar for SimpleBar {
     fn done(&mut self) {
-        self.finished = true;
+        self.job.done();
     }
 
     fn is_done(&self) -> bool {
-        self.finished
+        self.job.is_done()
     }
 
     /// Some implementation details:
@@ -51,47 +78,131 @@ impl crate::IsBar for SimpleBar {
     ///
     ///  if it cannot get the real term size, uses 81 as the size
     fn display(&mut self) -> String {
-        //TODO make this not use default
-        let width = crate::utils::term_width().unwrap_or(81);
-
-        let mut res =
-            String::with_capacity(width as usize /* starts out as a u16, so its fine */);
+        self.display_with(&crate::RenderContext::detect())
+    }
 
-        let percentage = self.progress * 100 / self.max_hint;
-        let bar_len = width - (50 + 5) - 2;
-        let bar_finished_len = (bar_len as f32 * percentage as f32 / 100.0) as i32;
+    fn display_with(&mut self, ctx: &crate::RenderContext) -> String {
+        self.job.sync();
+        let percentage = self.job.state.percentage();
+        let styles = self.styles.for_context(ctx);
+        if let Some(line) = self.job.final_line(&styles, ctx) {
+            return format!("\r{line}");
+        }
+        let mut layout = Layout(vec![
+            // pad to 50 chars on right
+            Part::Name {
+                text: self.job.name.clone(),
+                width: Some(50),
+                align: Align::Left,
+                style: styles.name,
+            },
+            Part::Text(styles.brackets.paint(START)),
+            Part::Bar(None),
+            Part::Text(styles.brackets.paint(END)),
+        ]);
+        if self.job.state.len().is_some() {
+            layout.0.extend([
+                //pad to 4 chars on left
+                Part::Text(pad(
+                    &styles.percentage.paint(&format!("{}{}", percentage, UNIT)),
+                    5,
+                    Align::Right,
+                )),
+                Part::Optional {
+                    text: format!(
+                        " {} eta {}",
+                        HumanDuration(self.job.state.elapsed()),
+                        format::eta(self.job.state.eta())
+                    ),
+                    priority: 0,
+                },
+            ]);
+        } else {
+            // no way to show how far along it is, so show how much has been done instead
+            layout.0.extend([
+                Part::Text(format!(
+                    " {}",
+                    styles.percentage.paint(&self.job.state.pos().to_string())
+                )),
+                Part::Optional {
+                    text: format!(" {:.1}/s", self.job.state.rate()),
+                    priority: 1,
+                },
+                Part::Optional {
+                    text: format!(" {}", HumanDuration(self.job.state.elapsed())),
+                    priority: 0,
+                },
+            ]);
+        }
 
+        let mut res = String::with_capacity(ctx.width);
         res += "\r";
+        res += &layout.render(ctx.width, |bar_len| {
+            if self.job.state.len().is_none() {
+                return indeterminate(self.job.state.elapsed(), bar_len, FILLED, EMPTY, &styles);
+            }
+            fill(
+                bar_len,
+                self.job.state.fraction().unwrap_or(0.0),
+                FILLED,
+                EMPTY,
+                &[],
+                &styles,
+            )
+        });
+        res
+    }
 
-        // pad to 50 chars on right
-        res += &format!("{:<50}", self.job_name);
-        res += START;
-        for _ in 0..bar_finished_len {
-            res += FILLED;
+    fn display_plain(&mut self) -> String {
+        self.job.sync();
+        if let Some(line) = self.job.final_plain() {
+            return line;
         }
-        for _ in bar_finished_len..i32::from(bar_len) {
-            res += EMPTY;
+        match self.job.state.len() {
+            Some(len) => format!(
+                "{}: {}% ({}/{})",
+                self.job.name,
+                self.job.state.percentage(),
+                self.job.state.pos(),
+                len
+            ),
+            None => format!("{}: {}", self.job.name, self.job.state.pos()),
         }
-        res += END;
-
-        //pad to 4 chars on left
-        res += &format!("{:>4}", percentage);
-        res += UNIT;
-
-        res
     }
 
     fn close_method(&self) -> crate::isbar::BarCloseMethod {
         crate::isbar::BarCloseMethod::LeaveBehind
     }
+
+    fn finish_state(&self) -> FinishState {
+        self.job.finish_state()
+    }
+
+    fn fraction(&self) -> Option<f64> {
+        self.job.state.fraction()
+    }
+
+    fn sync(&mut self) {
+        self.job.sync();
+    }
 }
 
 impl crate::subsets::IteratorProgress for SimpleBar {
     fn set_progress(&mut self, progress: usize) {
-        self.progress = progress;
+        self.job.state.set_pos(progress);
     }
 
     fn set_size_hint(&mut self, hint: usize) {
-        self.max_hint = hint;
+        self.job.state.set_len(hint);
+    }
+}
+
+impl super::job::HasJob for SimpleBar {
+    fn job(&self) -> &Job {
+        &self.job
+    }
+
+    fn job_mut(&mut self) -> &mut Job {
+        &mut self.job
     }
 }
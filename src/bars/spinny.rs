@@ -1,84 +1,219 @@
-use crate::{BarCloseMethod, IsBar};
+use std::time::Duration;
+
+use super::job::Job;
+use super::layout::{pad, Align, Layout, Part};
+use crate::clock::{Clock, SharedClock};
+use crate::format::HumanDuration;
+use crate::style::BarStyles;
+use crate::utils::display_width;
+use crate::{BarCloseMethod, FinishState, IsBar, ProgressState};
+
+/// Frame shown by a spinner when it is finished, unless it is given a different one with [`SpinniBuilder::finished_frame`]
+pub const DEFAULT_FINISHED_FRAME: &str = "•";
+
+/// Named sets of frames for [`SpinniBuilder::preset`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[non_exhaustive]
+pub enum Preset {
+    /// A dot tracing a path through a braille cell, leaving a trail behind it (the default)
+    #[default]
+    Braille,
+    /// Braille dots spinning in a circle: `⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏`
+    Dots,
+    /// A spinning line: `-\|/`
+    Line,
+    /// A spinning arc: `◜◠◝◞◡◟`
+    Arc,
+    /// A block bouncing back and forth in a bar, like `[=== ]`
+    BouncingBar,
+    /// A clock face going around (these are two columns wide)
+    Clock,
+    /// An arrow going around: `←↖↑↗→↘↓↙`
+    Arrows,
+}
+
+impl Preset {
+    /// The frames of this preset, in order
+    #[must_use]
+    pub const fn frames(self) -> &'static [&'static str] {
+        match self {
+            Self::Braille => &[
+                "⠁", "⠁", "⠉", "⠙", "⠚", "⠒", "⠂", "⠂", "⠒", "⠲", "⠴", "⠤", "⠄", "⠄", "⠤", "⠠",
+                "⠠", "⠤", "⠦", "⠖", "⠒", "⠐", "⠐", "⠒", "⠓", "⠋", "⠉", "⠈", "⠈", " ",
+            ],
+            Self::Dots => &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"],
+            Self::Line => &["-", "\\", "|", "/"],
+            Self::Arc => &["◜", "◠", "◝", "◞", "◡", "◟"],
+            Self::BouncingBar => &[
+                "[    ]", "[=   ]", "[==  ]", "[=== ]", "[ ===]", "[  ==]", "[   =]", "[    ]",
+                "[   =]", "[  ==]", "[ ===]", "[====]", "[=== ]", "[==  ]", "[=   ]",
+            ],
+            Self::Clock => &[
+                "🕛", "🕐", "🕑", "🕒", "🕓", "🕔", "🕕", "🕖", "🕗", "🕘", "🕙", "🕚",
+            ],
+            Self::Arrows => &["←", "↖", "↑", "↗", "→", "↘", "↓", "↙"],
+        }
+    }
+}
 
 /// Spinny spinning spinner
+///
+/// to construct, use [`SpinniBuilder`]
 #[derive(Clone, Debug, Hash)]
 pub struct Spinni {
-    tick_strings: Vec<char>,
-    current_char: usize,
-    job_name: String,
-    subtask: String,
-    done: bool,
+    frames: Vec<String>,
+    current_frame: usize,
+    /// shown instead of the current frame once the spinner is done
+    finished_frame: String,
+    /// the message is the subtask. spinners have no length, so the progress is only used for the elapsed time
+    job: Job,
     close_method: BarCloseMethod,
     tick_on_display: bool,
+    /// when set, the frame moves forward once every interval, on top of any ticks.
+    /// the time comes from the job's elapsed time, so it uses the same clock
+    interval: Option<Duration>,
+    /// only the name and message (subtask) styles are used
+    styles: BarStyles,
 }
 
 impl Spinni {
-    pub(crate) fn new(
-        job_name: String,
-        subtask: String,
-        close_method: BarCloseMethod,
-        tick_on_display: bool,
-    ) -> Self
-    where
-        Self: Sized,
-    {
-        Self {
-            tick_strings: "⠁⠁⠉⠙⠚⠒⠂⠂⠒⠲⠴⠤⠄⠄⠤⠠⠠⠤⠦⠖⠒⠐⠐⠒⠓⠋⠉⠈⠈ ".chars().collect(),
-            current_char: 0,
-            job_name,
-            subtask,
-            done: false,
-            close_method,
-            tick_on_display,
-        }
-    }
-
     pub fn set_job(&mut self, job_name: String) {
-        self.job_name = job_name;
+        self.job.name = job_name;
     }
 
     pub fn set_subtask(&mut self, task_name: String) {
-        self.subtask = task_name;
+        self.job.message = Some(task_name);
     }
 
     /// spin the wheel
     pub fn tick(&mut self) {
-        if self.current_char == self.tick_strings.len() - 1 {
-            self.current_char = 0;
+        self.current_frame = (self.current_frame + 1) % self.frames.len();
+    }
+
+    /// Which frame the spinner is on, counting ticks and how many intervals have passed
+    fn frame_index(&self) -> usize {
+        let Some(interval) = self.interval else {
+            return self.current_frame;
+        };
+        let elapsed = self.job.state.elapsed();
+        let steps = elapsed.as_nanos() / interval.as_nanos().max(1);
+        // the remainder is less than the number of frames, so it always fits
+        ((steps + self.current_frame as u128) % self.frames.len() as u128) as usize
+    }
+
+    /// The frame to draw. frames are padded to the width of the widest one, so the line does not jump around
+    fn frame(&self) -> String {
+        let frame = if self.job.is_done() {
+            &self.finished_frame
         } else {
-            self.current_char += 1;
-        }
+            &self.frames[self.frame_index()]
+        };
+        let width = self
+            .frames
+            .iter()
+            .chain([&self.finished_frame])
+            .map(|frame| display_width(frame))
+            .max()
+            .unwrap_or(0);
+        pad(frame, width, Align::Left)
     }
 }
 
 impl IsBar for Spinni {
     fn done(&mut self) {
-        self.done = true;
+        self.job.done();
     }
 
     fn is_done(&self) -> bool {
-        self.done
+        self.job.is_done()
+    }
+
+    fn finish_state(&self) -> FinishState {
+        self.job.finish_state()
     }
 
     fn close_method(&self) -> crate::BarCloseMethod {
         self.close_method
     }
 
+    fn display_plain(&mut self) -> String {
+        if let Some(line) = self.job.final_plain() {
+            return line;
+        }
+        format!(
+            "{}: {}",
+            self.job.name,
+            self.job.message.as_deref().unwrap_or_default()
+        )
+    }
+
     fn display(&mut self) -> String {
-        if self.tick_on_display {
+        self.display_with(&crate::RenderContext::detect())
+    }
+
+    fn display_with(&mut self, ctx: &crate::RenderContext) -> String {
+        if self.tick_on_display && !self.job.is_done() {
             self.tick();
         }
-        let spini_step = self.tick_strings[self.current_char];
-        format!("{} {}: {}", spini_step, self.job_name, self.subtask)
+        let styles = self.styles.for_context(ctx);
+        if let Some(line) = self.job.final_line(&styles, ctx) {
+            return line;
+        }
+        Layout(vec![
+            Part::Text(format!("{} ", self.frame())),
+            Part::Name {
+                text: self.job.name.clone(),
+                width: None,
+                align: Align::Left,
+                style: styles.name,
+            },
+            Part::Text(format!(
+                ": {}",
+                styles
+                    .message
+                    .paint(self.job.message.as_deref().unwrap_or_default())
+            )),
+            Part::Optional {
+                text: format!(" ({})", HumanDuration(self.job.state.elapsed())),
+                priority: 0,
+            },
+        ])
+        .render(ctx.width, |_| String::new())
     }
 }
 
+/// Builder pattern builder for [`Spinni`]
+///
+/// ```rust
+/// use stati::bars::SpinniBuilder;
+/// use stati::prelude::*;
+/// use stati::RenderContext;
+///
+/// # fn main() {
+/// let mut spinner = SpinniBuilder::new("Loading".into())
+///     .task_name("files".into())
+///     .frames(["a", "bb"])
+///     .finished_frame("done")
+///     .build();
+/// let ctx = RenderContext::new(80);
+/// // the spinner ticks before it is drawn
+/// assert_eq!(spinner.display_with(&ctx), "bb   Loading: files (0s)");
+/// assert_eq!(spinner.display_with(&ctx), "a    Loading: files (0s)");
+/// spinner.done();
+/// assert_eq!(spinner.display_with(&ctx), "done Loading: files (0s)");
+/// # }
+/// ```
 #[derive(Clone, Debug, Hash)]
 pub struct SpinniBuilder {
     job_name: String,
     task_name: String,
+    frames: Vec<String>,
+    finished_frame: String,
     close_method: BarCloseMethod,
     tick_on_display: bool,
+    interval: Option<Duration>,
+    clock: SharedClock,
+    styles: BarStyles,
 }
 
 impl SpinniBuilder {
@@ -87,8 +222,13 @@ impl SpinniBuilder {
         Self {
             job_name: name,
             task_name: "".into(),
+            frames: Vec::new(),
+            finished_frame: DEFAULT_FINISHED_FRAME.into(),
             close_method: BarCloseMethod::LeaveBehind,
             tick_on_display: true,
+            interval: None,
+            clock: SharedClock::default(),
+            styles: BarStyles::default(),
         }
     }
 
@@ -99,6 +239,28 @@ impl SpinniBuilder {
         self
     }
 
+    /// Sets the frames the spinner goes through. frames can be any string, and are padded to the width of the widest one
+    ///
+    /// if there are no frames, the [default preset](Preset::Braille) is used
+    #[must_use]
+    pub fn frames(mut self, frames: impl IntoIterator<Item = impl ToString>) -> Self {
+        self.frames = frames.into_iter().map(|frame| frame.to_string()).collect();
+        self
+    }
+
+    /// Uses the frames from a [`Preset`]
+    #[must_use]
+    pub fn preset(self, preset: Preset) -> Self {
+        self.frames(preset.frames())
+    }
+
+    /// Sets the frame shown once the spinner is done, instead of whichever frame it was on ([`DEFAULT_FINISHED_FRAME`] by default)
+    #[must_use]
+    pub fn finished_frame(mut self, frame: impl ToString) -> Self {
+        self.finished_frame = frame.to_string();
+        self
+    }
+
     #[must_use]
     pub const fn close_method(mut self, close_method: BarCloseMethod) -> Self {
         self.close_method = close_method;
@@ -111,13 +273,111 @@ impl SpinniBuilder {
         self
     }
 
+    /// Moves to the next frame every `interval`, so the spinner goes at the same speed no matter how often it is drawn.
+    ///
+    /// this turns off [`tick_on_display`](Self::tick_on_display), though [`tick`](Spinni::tick) can still be used to skip frames
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use stati::bars::SpinniBuilder;
+    /// use stati::clock::ManualClock;
+    /// use stati::prelude::*;
+    /// use stati::RenderContext;
+    ///
+    /// # fn main() {
+    /// let clock = ManualClock::new();
+    /// let mut spinner = SpinniBuilder::new("Loading".into())
+    ///     .frames(["a", "b", "c"])
+    ///     .interval(Duration::from_millis(100))
+    ///     .clock(clock.clone())
+    ///     .build();
+    /// let ctx = RenderContext::new(80);
+    /// assert!(spinner.display_with(&ctx).starts_with("a "));
+    /// // drawing again does not move the spinner
+    /// assert!(spinner.display_with(&ctx).starts_with("a "));
+    /// clock.advance(Duration::from_millis(250));
+    /// assert!(spinner.display_with(&ctx).starts_with("c "));
+    /// clock.advance(Duration::from_millis(100));
+    /// assert!(spinner.display_with(&ctx).starts_with("a "));
+    /// # }
+    /// ```
+    #[must_use]
+    pub const fn interval(mut self, interval: Duration) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+
+    /// Sets where the time comes from, for [`interval`](Self::interval) and the elapsed time shown after the subtask.
+    /// this is the real time by default
+    ///
+    /// see [`clock`](crate::clock) for more
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use stati::bars::SpinniBuilder;
+    /// use stati::clock::ManualClock;
+    /// use stati::prelude::*;
+    /// use stati::RenderContext;
+    ///
+    /// # fn main() {
+    /// let clock = ManualClock::new();
+    /// let mut spinner = SpinniBuilder::new("Loading".into())
+    ///     .frames(["-"])
+    ///     .task_name("files".into())
+    ///     .clock(clock.clone())
+    ///     .build();
+    /// clock.advance(Duration::from_secs(83));
+    /// assert_eq!(spinner.display_with(&RenderContext::new(80)), "- Loading: files (1m23s)");
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = SharedClock::new(clock);
+        self
+    }
+
+    /// Sets the colors and styles of the spinner (see [`style`](crate::style)).
+    /// only the name and message styles are used, with the message being the subtask
+    #[must_use]
+    pub const fn styles(mut self, styles: BarStyles) -> Self {
+        self.styles = styles;
+        self
+    }
+
     #[must_use]
     pub fn build(self) -> Spinni {
-        Spinni::new(
-            self.job_name,
-            self.task_name,
-            self.close_method,
-            self.tick_on_display,
-        )
+        let frames = if self.frames.is_empty() {
+            Preset::default()
+                .frames()
+                .iter()
+                .map(|&frame| frame.into())
+                .collect()
+        } else {
+            self.frames
+        };
+        let mut state = ProgressState::new(0);
+        state.set_clock(self.clock);
+        Spinni {
+            frames,
+            current_frame: 0,
+            finished_frame: self.finished_frame,
+            job: Job::new(self.job_name, state).with_message(self.task_name),
+            close_method: self.close_method,
+            tick_on_display: self.tick_on_display && self.interval.is_none(),
+            interval: self.interval,
+            styles: self.styles,
+        }
+    }
+}
+
+impl super::job::HasJob for Spinni {
+    fn job(&self) -> &Job {
+        &self.job
+    }
+
+    fn job_mut(&mut self) -> &mut Job {
+        &mut self.job
     }
 }
@@ -0,0 +1,625 @@
+//! A bar whose layout is described by a template string, like `{name} [{bar:40}] {pos}/{len} {eta}`
+//!
+//! ## Placeholders
+//! - `{name}` (or `{prefix}`): the name of the job
+//! - `{bar}`: the bar itself
+//! - `{percent}`: how far along the bar is, from 0 to 100
+//! - `{pos}`: the current progress
+//! - `{len}`: the size hint
+//! - `{rate}`: progress per second, averaged over the last few seconds (see [`Builder::rate_window`])
+//! - `{elapsed}`: how long the bar has existed for, like `1m23s`
+//! - `{eta}`: roughly how long until the bar is finished, like `1m23s` (or `?` if it cannot tell)
+//! - `{msg}` (or `{message}`): the message set with [`TemplateBar::set_message`]
+//! - `{bytes}`, `{total_bytes}` and `{bytes_per_sec}`: the progress, size hint and rate as bytes, like `12.3 MiB` or `4.5 MiB/s`
+//!
+//! `{pos}`, `{len}` and `{rate}` are formatted with the bar's [units](Builder::units), which are plain numbers by default
+//!
+//! if the size hint is 0, the length is unknown: `{percent}` and `{len}` show `?`,
+//! and `{bar}` shows a section bouncing back and forth until a size hint is given
+//!
+//! placeholders can have a width and alignment, using the same syntax as [`format!`]:
+//! `{name:20}` or `{name:<20}` pads the name to 20 columns on the right, `{pos:>5}` pads it on the left
+//! and `{msg:^10}` centers it. values longer than the width are not cut off.
+//!
+//! for `{bar}`, the width is how long the bar is. without one, the bar takes up all the space
+//! left over by the rest of the template (shared equally if there are multiple bars)
+//!
+//! when the terminal is too narrow for everything, the bar is shrunk first, then the name is cut off with `…`,
+//! and then `{rate}`, `{bytes_per_sec}`, `{elapsed}`, `{eta}` and `{msg}` are left out, starting from the right
+//!
+//! to include a literal `{` or `}`, use `{{` or `}}`
+//!
+//! widths are in terminal columns, so wide characters (like CJK or emoji) count as two.
+//! the filled and empty parts of the bar can be wide as well
+//!
+//! ```rust
+//! use stati::bars::template;
+//! use stati::prelude::*;
+//! use stati::RenderContext;
+//!
+//! # fn main() {
+//! let mut bar = template::Builder::new("{name:6}[{bar:8}]")
+//!     .name("下载")
+//!     .filled("🟩")
+//!     .hint(2)
+//!     .build()
+//!     .unwrap();
+//! bar.set_progress(1);
+//! assert_eq!(bar.display_with(&RenderContext::new(80)), "\r下载  [🟩🟩----]");
+//! # }
+//! ```
+//!
+//! ## Example
+//!
+//! ```rust
+//! use stati::bars::template;
+//! use stati::prelude::*;
+//! use stati::RenderContext;
+//!
+//! # fn main() {
+//! let mut bar = template::Builder::new("{name} [{bar:10}] {pos:>3}/{len}")
+//!     .name("Downloading")
+//!     .hint(40)
+//!     .build()
+//!     .unwrap();
+//! bar.set_progress(20);
+//! assert_eq!(
+//!     bar.display_with(&RenderContext::new(80)),
+//!     "\rDownloading [=====-----]  20/40"
+//! );
+//!
+//! // on narrow terminals, the line is shrunk to fit
+//! assert_eq!(
+//!     bar.display_with(&RenderContext::new(20)),
+//!     "\rDow… [==---]  20/40"
+//! );
+//! assert!(bar.display_with(&RenderContext::new(5)).chars().count() <= 5);
+//!
+//! // plain lines (for logs and files) are laid out for 81 columns, whatever the terminal's size
+//! let mut wide = template::Builder::new("{name} [{bar}] {percent}%").name("Job").hint(4).build().unwrap();
+//! wide.set_progress(1);
+//! assert_eq!(wide.display_plain(), format!("Job [{}{}] 25%", "=".repeat(17), "-".repeat(53)));
+//!
+//! // mistakes in the template are reported when building the bar
+//! assert!(template::Builder::new("{name} {nope}").build().is_err());
+//! # }
+//! ```
+
+use std::fmt;
+use std::time::Duration;
+
+use super::job::Job;
+use super::layout::{self, Align, Layout, Part};
+use crate::format::{self, HumanBytes, HumanDuration, Units};
+use crate::style::{BarStyles, Style};
+use crate::{FinishState, ProgressState};
+
+mod default {
+    pub const FILLED: &str = "=";
+    pub const EMPTY: &str = "-";
+}
+
+/// A value that can be put in a template
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum Key {
+    Name,
+    Bar,
+    Percent,
+    Pos,
+    Len,
+    Rate,
+    Elapsed,
+    Eta,
+    Message,
+    Bytes,
+    TotalBytes,
+    BytesPerSec,
+}
+
+impl Key {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "name" | "prefix" => Self::Name,
+            "bar" => Self::Bar,
+            "percent" => Self::Percent,
+            "pos" => Self::Pos,
+            "len" => Self::Len,
+            "rate" => Self::Rate,
+            "elapsed" => Self::Elapsed,
+            "eta" => Self::Eta,
+            "msg" | "message" => Self::Message,
+            "bytes" => Self::Bytes,
+            "total_bytes" => Self::TotalBytes,
+            "bytes_per_sec" => Self::BytesPerSec,
+            _ => return None,
+        })
+    }
+}
+
+/// One piece of a parsed template
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+enum Segment {
+    Text(String),
+    Field {
+        key: Key,
+        width: Option<usize>,
+        align: Align,
+    },
+}
+
+/// A problem with a template, found when building a [`TemplateBar`]
+///
+/// positions are byte offsets into the template
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub enum TemplateError {
+    /// A `{` with no `}` after it
+    Unclosed { pos: usize },
+    /// A `}` with no `{` before it (use `}}` for a literal `}`)
+    Unopened { pos: usize },
+    /// A placeholder that is not one of the ones supported
+    UnknownPlaceholder { pos: usize, name: String },
+    /// The part after the `:` in a placeholder is not a valid alignment and width
+    InvalidSpec { pos: usize, spec: String },
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unclosed { pos } => write!(f, "unclosed `{{` at {pos}"),
+            Self::Unopened { pos } => write!(f, "unmatched `}}` at {pos}"),
+            Self::UnknownPlaceholder { pos, name } => {
+                write!(f, "unknown placeholder `{name}` at {pos}")
+            }
+            Self::InvalidSpec { pos, spec } => {
+                write!(f, "invalid width or alignment `{spec}` at {pos}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// Splits a template into text and placeholders
+fn parse(template: &str) -> Result<Vec<Segment>, TemplateError> {
+    let mut segments = vec![];
+    let mut text = String::new();
+    let mut chars = template.char_indices().peekable();
+    while let Some((pos, ch)) = chars.next() {
+        match ch {
+            '{' if chars.next_if(|&(_, ch)| ch == '{').is_some() => text.push('{'),
+            '}' if chars.next_if(|&(_, ch)| ch == '}').is_some() => text.push('}'),
+            '}' => return Err(TemplateError::Unopened { pos }),
+            '{' => {
+                let len = template[pos..]
+                    .find('}')
+                    .ok_or(TemplateError::Unclosed { pos })?;
+                let inner = &template[pos + 1..pos + len];
+                // skip to after the closing brace
+                while chars.next_if(|&(i, _)| i <= pos + len).is_some() {}
+                if !text.is_empty() {
+                    segments.push(Segment::Text(std::mem::take(&mut text)));
+                }
+                segments.push(parse_field(pos, inner)?);
+            }
+            ch => text.push(ch),
+        }
+    }
+    if !text.is_empty() {
+        segments.push(Segment::Text(text));
+    }
+    Ok(segments)
+}
+
+/// Parses the inside of a placeholder, like `name:<20`
+fn parse_field(pos: usize, inner: &str) -> Result<Segment, TemplateError> {
+    let (name, spec) = inner.split_once(':').unwrap_or((inner, ""));
+    let key = Key::parse(name.trim()).ok_or_else(|| TemplateError::UnknownPlaceholder {
+        pos,
+        name: name.into(),
+    })?;
+    let (align, width) = match spec.chars().next() {
+        Some('<') => (Align::Left, &spec[1..]),
+        Some('>') => (Align::Right, &spec[1..]),
+        Some('^') => (Align::Center, &spec[1..]),
+        _ => (Align::Left, spec),
+    };
+    let width = if width.is_empty() {
+        None
+    } else {
+        Some(width.parse().map_err(|_| TemplateError::InvalidSpec {
+            pos,
+            spec: spec.into(),
+        })?)
+    };
+    Ok(Segment::Field { key, width, align })
+}
+
+/// A progress bar with a layout described by a template string.
+///
+/// To construct, use [`Builder`]. see the [module docs](self) for the template syntax
+///
+/// [`Builder`]: Builder
+#[derive(Clone, Debug, Hash)]
+pub struct TemplateBar {
+    /// always has a message, for `{msg}`
+    job: Job,
+    filled: String,
+    empty: String,
+    partials: Vec<String>,
+    units: Units,
+    styles: BarStyles,
+    segments: Vec<Segment>,
+}
+
+impl TemplateBar {
+    pub fn set_name(&mut self, job_name: String) {
+        self.job.name = job_name;
+    }
+
+    /// Sets the message shown by `{msg}`
+    pub fn set_message(&mut self, message: impl ToString) {
+        self.job.message = Some(message.to_string());
+    }
+
+    /// Formats a value for a placeholder (other than `{bar}`)
+    fn value(&self, key: Key) -> String {
+        match key {
+            Key::Name => self.job.name.clone(),
+            Key::Percent => match self.job.state.len() {
+                Some(_) => self.job.state.percentage().to_string(),
+                None => "?".into(),
+            },
+            Key::Pos => self.units.format(self.job.state.pos() as u64),
+            Key::Len => self
+                .job
+                .state
+                .len()
+                .map_or("?".into(), |len| self.units.format(len as u64)),
+            Key::Rate => self.units.format_rate(self.job.state.rate()),
+            Key::Bytes => HumanBytes(self.job.state.pos() as u64).to_string(),
+            Key::TotalBytes => self
+                .job
+                .state
+                .len()
+                .map_or("?".into(), |len| HumanBytes(len as u64).to_string()),
+            Key::BytesPerSec => format!(
+                "{}/s",
+                Units::BinaryBytes.format_rate(self.job.state.rate())
+            ),
+            Key::Elapsed => HumanDuration(self.job.state.elapsed()).to_string(),
+            Key::Eta => format::eta(self.job.state.eta()),
+            Key::Message => self.job.message.clone().unwrap_or_default(),
+            Key::Bar => unreachable!("bars are formatted separately"),
+        }
+    }
+
+    /// Formats `{bar}`, `len` characters long
+    fn bar(&self, len: usize, styles: &BarStyles) -> String {
+        if self.job.state.len().is_none() {
+            return layout::indeterminate(
+                self.job.state.elapsed(),
+                len,
+                &self.filled,
+                &self.empty,
+                styles,
+            );
+        }
+        layout::fill(
+            len,
+            self.job.state.fraction().unwrap_or(0.0),
+            &self.filled,
+            &self.empty,
+            &self.partials,
+            styles,
+        )
+    }
+}
+
+impl crate::IsBar for TemplateBar {
+    fn done(&mut self) {
+        self.job.done();
+    }
+
+    fn is_done(&self) -> bool {
+        self.job.is_done()
+    }
+
+    /// Some implementation details:
+    ///
+    /// starts with "\r" and has no end char
+    ///
+    ///  if it cannot get the real term size, uses 81 as the size
+    fn display(&mut self) -> String {
+        self.display_with(&crate::RenderContext::detect())
+    }
+
+    fn display_with(&mut self, ctx: &crate::RenderContext) -> String {
+        self.job.sync();
+        let styles = self.styles.for_context(ctx);
+        // the final line replaces the template, so it looks the same as the other bars
+        if let Some(line) = self.job.final_line(&styles, ctx) {
+            return format!("\r{line}");
+        }
+        let layout = Layout(
+            self.segments
+                .iter()
+                .map(|segment| match *segment {
+                    Segment::Text(ref text) => Part::Text(text.clone()),
+                    Segment::Field {
+                        key: Key::Bar,
+                        width,
+                        ..
+                    } => Part::Bar(width),
+                    Segment::Field {
+                        key: Key::Name,
+                        width,
+                        align,
+                    } => Part::Name {
+                        text: self.job.name.clone(),
+                        width,
+                        align,
+                        style: styles.name,
+                    },
+                    Segment::Field { key, width, align } => {
+                        let style = match key {
+                            Key::Percent => styles.percentage,
+                            Key::Message => styles.message,
+                            _ => Style::new(),
+                        };
+                        let value = style.paint(&self.value(key));
+                        let text =
+                            width.map_or(value.clone(), |width| layout::pad(&value, width, align));
+                        match key {
+                            Key::Rate
+                            | Key::BytesPerSec
+                            | Key::Elapsed
+                            | Key::Eta
+                            | Key::Message => Part::Optional { text, priority: 0 },
+                            _ => Part::Text(text),
+                        }
+                    }
+                })
+                .collect(),
+        );
+
+        let mut res = String::with_capacity(ctx.width);
+        res += "\r";
+        res += &layout.render(ctx.width, |len| self.bar(len, &styles));
+        res
+    }
+
+    fn display_plain(&mut self) -> String {
+        self.job.sync();
+        if let Some(line) = self.job.final_plain() {
+            return line;
+        }
+        // plain output is usually not going to a terminal, so its width is not detected
+        let ctx = crate::RenderContext::new(crate::utils::DEFAULT_WIDTH);
+        crate::utils::strip_ansi(&self.display_with(&ctx))
+    }
+
+    fn close_method(&self) -> crate::isbar::BarCloseMethod {
+        crate::isbar::BarCloseMethod::LeaveBehind
+    }
+
+    fn finish_state(&self) -> FinishState {
+        self.job.finish_state()
+    }
+
+    fn fraction(&self) -> Option<f64> {
+        self.job.state.fraction()
+    }
+
+    fn sync(&mut self) {
+        self.job.sync();
+    }
+}
+
+impl crate::subsets::IteratorProgress for TemplateBar {
+    fn set_progress(&mut self, progress: usize) {
+        self.job.state.set_pos(progress);
+    }
+
+    fn set_size_hint(&mut self, hint: usize) {
+        self.job.state.set_len(hint);
+    }
+}
+
+/// Builder pattern builder for [`TemplateBar`]
+///
+/// to create the builder, use [`new`] and to construct the bar use [`build`]
+///
+/// [`TemplateBar`]: TemplateBar
+/// [`new`]: Builder::new
+/// [`build`]: Builder::build
+#[derive(Clone, Debug, Hash)]
+pub struct Builder {
+    template: String,
+    job_name: String,
+    hint: usize,
+    filled: String,
+    empty: String,
+    partials: Vec<String>,
+    rate_window: Duration,
+    units: Units,
+    styles: BarStyles,
+}
+
+impl Builder {
+    /// Create a new [`Builder`], for a bar using `template` as its layout
+    #[must_use]
+    pub fn new(template: impl ToString) -> Self {
+        Self {
+            template: template.to_string(),
+            job_name: String::new(),
+            hint: 100,
+            filled: default::FILLED.to_string(),
+            empty: default::EMPTY.to_string(),
+            partials: vec![],
+            rate_window: crate::state::DEFAULT_RATE_WINDOW,
+            units: Units::Plain,
+            styles: BarStyles::default(),
+        }
+    }
+
+    /// Sets the name of the job
+    #[must_use]
+    pub fn name(mut self, name: impl ToString) -> Self {
+        self.job_name = name.to_string();
+        self
+    }
+
+    /// Sets the size hint. if this is 0, the length is unknown (see the [module docs](self))
+    #[must_use]
+    pub fn hint(mut self, hint: usize) -> Self {
+        self.hint = hint;
+        self
+    }
+
+    /// Set the string for the filled bar section (is repeated, and can be more than one column wide)
+    #[must_use]
+    pub fn filled(mut self, v: impl ToString) -> Self {
+        self.filled = v.to_string();
+        self
+    }
+
+    /// Set the string for the empty bar section (is repeated, and can be more than one column wide)
+    #[must_use]
+    pub fn empty(mut self, v: impl ToString) -> Self {
+        self.empty = v.to_string();
+        self
+    }
+
+    /// Set the glyphs drawn at the edge of the filled bar section, to show how full that cell is.
+    ///
+    /// these go from least to most filled, with the last one being a full cell (like `▏▎▍▌▋▊▉█`),
+    /// and should be as wide as the filled string. by default there are none, so the bar fills one cell at a time
+    ///
+    /// ```rust
+    /// use stati::bars::template::Builder;
+    /// use stati::prelude::*;
+    /// use stati::RenderContext;
+    ///
+    /// # fn main() {
+    /// let mut bar = Builder::new("[{bar:8}]")
+    ///     .filled("█")
+    ///     .empty(" ")
+    ///     .partials("▏▎▍▌▋▊▉█".chars())
+    ///     .hint(1000)
+    ///     .build()
+    ///     .unwrap();
+    /// bar.set_progress(237);
+    /// // 23.7% of 8 columns is 1.896 columns
+    /// assert_eq!(bar.display_with(&RenderContext::new(80)), "\r[█▉      ]");
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn partials(mut self, partials: impl IntoIterator<Item = impl ToString>) -> Self {
+        self.partials = partials.into_iter().map(|p| p.to_string()).collect();
+        self
+    }
+
+    /// Sets how far back `{rate}` and `{eta}` look, see [`ProgressState::set_window`]
+    #[must_use]
+    pub const fn rate_window(mut self, window: Duration) -> Self {
+        self.rate_window = window;
+        self
+    }
+
+    /// Sets how `{pos}`, `{len}` and `{rate}` are formatted
+    ///
+    /// ```rust
+    /// use stati::bars::template::Builder;
+    /// use stati::format::Units;
+    /// use stati::prelude::*;
+    /// use stati::RenderContext;
+    ///
+    /// # fn main() {
+    /// let mut bar = Builder::new("{pos} / {len}")
+    ///     .units(Units::Si)
+    ///     .hint(3_400_000)
+    ///     .build()
+    ///     .unwrap();
+    /// bar.set_progress(1_234);
+    /// assert_eq!(bar.display_with(&RenderContext::new(80)), "\r1.2k / 3.4M");
+    ///
+    /// let mut bar = Builder::new("{bytes} / {total_bytes}")
+    ///     .hint(1_288_490_189)
+    ///     .build()
+    ///     .unwrap();
+    /// bar.set_progress(12_897_484);
+    /// assert_eq!(bar.display_with(&RenderContext::new(80)), "\r12.3 MiB / 1.2 GiB");
+    /// # }
+    /// ```
+    #[must_use]
+    pub const fn units(mut self, units: Units) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// Sets the colors and styles of the bar (see [`style`](crate::style))
+    ///
+    /// `{name}`, `{bar}`, `{percent}` and `{msg}` are styled. text that is part of the template is not,
+    /// so `brackets` is ignored
+    ///
+    /// ```rust
+    /// use stati::bars::template::Builder;
+    /// use stati::prelude::*;
+    /// use stati::style::{BarStyles, Color, Style};
+    /// use stati::RenderContext;
+    ///
+    /// # fn main() {
+    /// let mut bar = Builder::new("{name} [{bar:4}] {percent}%")
+    ///     .name("Job")
+    ///     .styles(BarStyles {
+    ///         filled: Style::new().fg(Color::Green),
+    ///         percentage: Style::new().bold(),
+    ///         ..BarStyles::default()
+    ///     })
+    ///     .hint(4)
+    ///     .build()
+    ///     .unwrap();
+    /// bar.set_progress(1);
+    /// assert_eq!(
+    ///     bar.display_with(&RenderContext::new(80).with_color(true)),
+    ///     "\rJob [\x1b[32m=\x1b[0m---] \x1b[1m25\x1b[0m%"
+    /// );
+    /// // without colors, the styles are left out
+    /// assert_eq!(bar.display_with(&RenderContext::new(80)), "\rJob [=---] 25%");
+    /// # }
+    /// ```
+    #[must_use]
+    pub const fn styles(mut self, styles: BarStyles) -> Self {
+        self.styles = styles;
+        self
+    }
+
+    /// Build the [`TemplateBar`]
+    ///
+    /// # Errors
+    /// if the template is not valid (see the [module docs](self))
+    pub fn build(self) -> Result<TemplateBar, TemplateError> {
+        let mut state = ProgressState::new(self.hint);
+        state.set_window(self.rate_window);
+        Ok(TemplateBar {
+            segments: parse(&self.template)?,
+            job: Job::new(self.job_name, state).with_message(String::new()),
+            filled: self.filled,
+            empty: self.empty,
+            partials: self.partials,
+            units: self.units,
+            styles: self.styles,
+        })
+    }
+}
+
+impl super::job::HasJob for TemplateBar {
+    fn job(&self) -> &Job {
+        &self.job
+    }
+
+    fn job_mut(&mut self) -> &mut Job {
+        &mut self.job
+    }
+}
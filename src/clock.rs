@@ -0,0 +1,115 @@
+//! Sources of the current time, so animations can be tested without waiting
+//!
+//! bars that animate over time (like [`Spinni`] with a frame interval) take a [`Clock`],
+//! which is [`SystemClock`] unless told otherwise. [`ManualClock`] only moves when it is told to,
+//! so tests can check exactly what is drawn
+//!
+//! [`Spinni`]: crate::bars::spinny::Spinni
+
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Something that tells the time
+pub trait Clock: Debug + Send + Sync {
+    /// The current time
+    fn now(&self) -> Instant;
+}
+
+/// The real time, from [`Instant::now`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that stands still until it is [advanced](ManualClock::advance).
+///
+/// clones share the same time, so one can be given to a bar while the other is used to move time forward
+///
+/// ```rust
+/// use std::time::Duration;
+/// use stati::clock::{Clock, ManualClock};
+///
+/// # fn main() {
+/// let clock = ManualClock::new();
+/// let start = clock.now();
+/// clock.clone().advance(Duration::from_secs(2));
+/// assert_eq!(clock.now() - start, Duration::from_secs(2));
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    start: Instant,
+    /// nanoseconds since `start`
+    offset: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+    /// Creates a new [`ManualClock`], starting at the current time
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            offset: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Moves the time forward by `by`
+    pub fn advance(&self, by: Duration) {
+        let nanos = u64::try_from(by.as_nanos()).unwrap_or(u64::MAX);
+        self.offset.fetch_add(nanos, Ordering::Relaxed);
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + Duration::from_nanos(self.offset.load(Ordering::Relaxed))
+    }
+}
+
+/// A [`Clock`] shared between clones of whatever uses it.
+///
+/// clocks are compared and hashed by which clock they are, not by what time it is
+#[derive(Clone, Debug)]
+pub(crate) struct SharedClock(Arc<dyn Clock>);
+
+impl SharedClock {
+    pub(crate) fn new(clock: impl Clock + 'static) -> Self {
+        Self(Arc::new(clock))
+    }
+
+    pub(crate) fn now(&self) -> Instant {
+        self.0.now()
+    }
+}
+
+impl Default for SharedClock {
+    fn default() -> Self {
+        Self::new(SystemClock)
+    }
+}
+
+impl PartialEq for SharedClock {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for SharedClock {}
+
+impl std::hash::Hash for SharedClock {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).cast::<()>().hash(state);
+    }
+}
@@ -0,0 +1,164 @@
+//! Lock-free progress updates, for hot loops and lots of worker threads
+//!
+//! updating a bar through a [`ThreadedBarWrapper`] locks it (and on stable rust, boxes the guard) every time.
+//! that is fine for a few updates, but adds up when many threads update it once per item.
+//!
+//! a [`ProgressCounter`] is a handle to a few atomics (position, length, and a message generation) that a bar reads when it is drawn,
+//! so [`inc`], [`set_pos`] and [`set_len`] are just an atomic add or store. get one from a bar with
+//! [`CounterProgress::counter`], and clone it into as many threads as you want
+//!
+//! only those three are lock-free. [`set_message`] allocates the new message and locks it while it is swapped in,
+//! so it is fine for the odd status update, but should be kept out of hot loops
+//!
+//! ```rust
+//! use stati::bars::SimpleBar;
+//! use stati::prelude::*;
+//! use stati::{BarManager, RenderMode};
+//!
+//! # fn main() {
+//! let mut manager = BarManager::with_writer(std::io::sink()).render_mode(RenderMode::Plain);
+//! let mut bar = manager.register_threadsafe(SimpleBar::new("Working", 0));
+//! let counter = bar.bar().counter();
+//! counter.set_len(400);
+//! std::thread::scope(|scope| {
+//!     for _ in 0..4 {
+//!         let counter = counter.clone();
+//!         scope.spawn(move || {
+//!             for _ in 0..100 {
+//!                 counter.inc(1);
+//!             }
+//!         });
+//!     }
+//! });
+//! counter.set_message("Done working");
+//! assert_eq!(bar.bar().display_plain(), "Done working: 100% (400/400)");
+//! # }
+//! ```
+//!
+//! [`ThreadedBarWrapper`]: crate::ThreadedBarWrapper
+//! [`inc`]: ProgressCounter::inc
+//! [`set_pos`]: ProgressCounter::set_pos
+//! [`set_len`]: ProgressCounter::set_len
+//! [`set_message`]: ProgressCounter::set_message
+//! [`CounterProgress::counter`]: crate::subsets::CounterProgress::counter
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+struct Shared {
+    pos: AtomicUsize,
+    /// 0 if the length is unknown
+    len: AtomicUsize,
+    /// bumped every time the message is set, so bars only look at the message when it changed
+    generation: AtomicU64,
+    message: Mutex<String>,
+}
+
+/// A cloneable handle for updating a bar's progress without locking it, see the [module docs](self)
+///
+/// all clones share the same progress. updating the position or length never locks or allocates
+#[derive(Clone)]
+pub struct ProgressCounter(Arc<Shared>);
+
+impl ProgressCounter {
+    /// Creates a counter for a job that is `len` items long (or of unknown length, if it is 0).
+    ///
+    /// this is not attached to any bar, so it is mostly useful for your own [`IsBar`](crate::IsBar)
+    /// implementations (see [`ProgressState::counter`](crate::ProgressState::counter))
+    #[must_use]
+    pub fn new(len: usize) -> Self {
+        Self(Arc::new(Shared {
+            pos: AtomicUsize::new(0),
+            len: AtomicUsize::new(len),
+            generation: AtomicU64::new(0),
+            message: Mutex::new(String::new()),
+        }))
+    }
+
+    /// Adds `delta` to the current position
+    pub fn inc(&self, delta: usize) {
+        self.0.pos.fetch_add(delta, Ordering::Relaxed);
+    }
+
+    /// Sets the current position (how many items are done)
+    pub fn set_pos(&self, pos: usize) {
+        self.0.pos.store(pos, Ordering::Relaxed);
+    }
+
+    /// Sets how many items there are in total. if this is 0, the length is unknown
+    pub fn set_len(&self, len: usize) {
+        self.0.len.store(len, Ordering::Relaxed);
+    }
+
+    /// The current position (how many items are done)
+    #[must_use]
+    pub fn pos(&self) -> usize {
+        self.0.pos.load(Ordering::Relaxed)
+    }
+
+    /// How many items there are in total, or `None` if it is unknown
+    #[must_use]
+    #[allow(clippy::len_without_is_empty)] // this is not a collection
+    pub fn len(&self) -> Option<usize> {
+        let len = self.0.len.load(Ordering::Relaxed);
+        (len != 0).then_some(len)
+    }
+
+    /// Sets the message shown by the bar. for most bars this replaces the name, and for [`TemplateBar`] it is `{msg}`
+    ///
+    /// unlike the position and length, this allocates, and briefly locks the message (but never the bar),
+    /// since it is expected to change much less often
+    ///
+    /// [`TemplateBar`]: crate::bars::TemplateBar
+    pub fn set_message(&self, message: impl ToString) {
+        let message = message.to_string();
+        *self.0.message.lock() = message;
+        self.0.generation.fetch_add(1, Ordering::Release);
+    }
+
+    /// How many times the message has been set
+    #[must_use]
+    pub fn message_generation(&self) -> u64 {
+        self.0.generation.load(Ordering::Acquire)
+    }
+
+    /// The current message
+    #[must_use]
+    pub fn message(&self) -> String {
+        self.0.message.lock().clone()
+    }
+
+    /// Checks if `self` and `other` are clones of the same counter
+    #[must_use]
+    pub fn same_as(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl fmt::Debug for ProgressCounter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressCounter")
+            .field("pos", &self.pos())
+            .field("len", &self.len())
+            .field("message_generation", &self.message_generation())
+            .finish()
+    }
+}
+
+impl PartialEq for ProgressCounter {
+    fn eq(&self, other: &Self) -> bool {
+        self.same_as(other)
+    }
+}
+
+impl Eq for ProgressCounter {}
+
+/// hashed by which counter it is, to match [`PartialEq`]
+impl std::hash::Hash for ProgressCounter {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).hash(state);
+    }
+}
@@ -0,0 +1,231 @@
+//! Human readable formatting for values shown on bars
+
+use std::fmt;
+use std::time::Duration;
+
+/// Formats a [`Duration`] in a short, human readable form, like `1m23s`
+///
+/// only whole seconds are shown, and hours are the largest unit used
+///
+/// ```rust
+/// use std::time::Duration;
+/// use stati::format::HumanDuration;
+///
+/// # fn main() {
+/// assert_eq!(HumanDuration(Duration::from_millis(5400)).to_string(), "5s");
+/// assert_eq!(HumanDuration(Duration::from_secs(83)).to_string(), "1m23s");
+/// assert_eq!(HumanDuration(Duration::from_secs(3723)).to_string(), "1h02m03s");
+/// # }
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct HumanDuration(pub Duration);
+
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.0.as_secs();
+        let (hours, mins, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+        if hours > 0 {
+            write!(f, "{hours}h{mins:02}m{secs:02}s")
+        } else if mins > 0 {
+            write!(f, "{mins}m{secs:02}s")
+        } else {
+            write!(f, "{secs}s")
+        }
+    }
+}
+
+/// Formats an ETA (like from [`ProgressState::eta`]) as a [`HumanDuration`], or `?` if it is not known
+///
+/// [`ProgressState::eta`]: crate::ProgressState::eta
+pub(crate) fn eta(eta: Option<Duration>) -> String {
+    eta.map_or_else(|| "?".into(), |eta| HumanDuration(eta).to_string())
+}
+
+/// Scales `value` down by `base` until it is less than `base`, formatting it with one decimal place and the unit for that scale.
+/// `None` if it is already less than `base`.
+///
+/// values are compared after rounding to one decimal place, so `999.96` is `1.0k` rather than `1000.0`
+fn scaled(value: f64, base: f64, units: &[&str], sep: &str) -> Option<String> {
+    let reaches_base = |value: f64| (value * 10.0).round() / 10.0 >= base;
+    if !reaches_base(value) {
+        return None;
+    }
+    let mut value = value;
+    let mut unit = 0;
+    while reaches_base(value) && unit < units.len() - 1 {
+        value /= base;
+        unit += 1;
+    }
+    Some(format!("{value:.1}{sep}{}", units[unit]))
+}
+
+const BINARY_UNITS: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+const DECIMAL_UNITS: &[&str] = &["B", "kB", "MB", "GB", "TB", "PB", "EB"];
+const SI_UNITS: &[&str] = &["", "k", "M", "G", "T", "P", "E"];
+
+/// Formats a number of bytes using binary (powers of 1024) units, like `12.3 MiB`
+///
+/// ```rust
+/// use stati::format::HumanBytes;
+///
+/// # fn main() {
+/// assert_eq!(HumanBytes(512).to_string(), "512 B");
+/// assert_eq!(HumanBytes(12_897_484).to_string(), "12.3 MiB");
+/// assert_eq!(HumanBytes(1_288_490_189).to_string(), "1.2 GiB");
+/// // rounding up to 1024 KiB moves up to the next unit
+/// assert_eq!(HumanBytes(1_048_552).to_string(), "1.0 MiB");
+/// # }
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct HumanBytes(pub u64);
+
+impl fmt::Display for HumanBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match scaled(self.0 as f64, 1024.0, BINARY_UNITS, " ") {
+            Some(scaled) => f.write_str(&scaled),
+            None => write!(f, "{} B", self.0),
+        }
+    }
+}
+
+/// Formats a number of bytes using decimal (powers of 1000) units, like `12.3 MB`
+///
+/// ```rust
+/// use stati::format::DecimalBytes;
+///
+/// # fn main() {
+/// assert_eq!(DecimalBytes(512).to_string(), "512 B");
+/// assert_eq!(DecimalBytes(12_345_678).to_string(), "12.3 MB");
+/// assert_eq!(DecimalBytes(999_960).to_string(), "1.0 MB");
+/// # }
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct DecimalBytes(pub u64);
+
+impl fmt::Display for DecimalBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match scaled(self.0 as f64, 1000.0, DECIMAL_UNITS, " ") {
+            Some(scaled) => f.write_str(&scaled),
+            None => write!(f, "{} B", self.0),
+        }
+    }
+}
+
+/// Formats a count with commas between each group of thousands, like `1,234,567`
+///
+/// ```rust
+/// use stati::format::HumanCount;
+///
+/// # fn main() {
+/// assert_eq!(HumanCount(999).to_string(), "999");
+/// assert_eq!(HumanCount(1_234_567).to_string(), "1,234,567");
+/// # }
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct HumanCount(pub u64);
+
+impl fmt::Display for HumanCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.0.to_string();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                f.write_str(",")?;
+            }
+            write!(f, "{digit}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats a count using SI prefixes, like `1.2k` or `3.4M`
+///
+/// ```rust
+/// use stati::format::SiCount;
+///
+/// # fn main() {
+/// assert_eq!(SiCount(999).to_string(), "999");
+/// assert_eq!(SiCount(1_234).to_string(), "1.2k");
+/// assert_eq!(SiCount(3_400_000).to_string(), "3.4M");
+/// assert_eq!(SiCount(999_960).to_string(), "1.0M");
+/// # }
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct SiCount(pub u64);
+
+impl fmt::Display for SiCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match scaled(self.0 as f64, 1000.0, SI_UNITS, "") {
+            Some(scaled) => f.write_str(&scaled),
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
+/// How a bar formats its position, length and rate
+///
+/// ```rust
+/// use stati::format::Units;
+///
+/// # fn main() {
+/// assert_eq!(Units::BinaryBytes.format(12_897_484), "12.3 MiB");
+/// assert_eq!(Units::BinaryBytes.format_rate(4_718_592.0), "4.5 MiB");
+/// assert_eq!(Units::Si.format_rate(12.34), "12.3");
+/// assert_eq!(Units::Si.format_rate(999.96), "1.0k");
+/// assert_eq!(Units::Plain.format(1234), "1234");
+/// # }
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Units {
+    /// Plain numbers, like `1234567`
+    #[default]
+    Plain,
+    /// Thousands separated with commas, like `1,234,567` (see [`HumanCount`])
+    Thousands,
+    /// SI prefixes, like `1.2M` (see [`SiCount`])
+    Si,
+    /// Bytes in binary units, like `1.2 MiB` (see [`HumanBytes`])
+    BinaryBytes,
+    /// Bytes in decimal units, like `1.2 MB` (see [`DecimalBytes`])
+    DecimalBytes,
+}
+
+impl Units {
+    /// Formats an amount, like a position or length
+    #[must_use]
+    pub fn format(self, value: u64) -> String {
+        match self {
+            Self::Plain => value.to_string(),
+            Self::Thousands => HumanCount(value).to_string(),
+            Self::Si => SiCount(value).to_string(),
+            Self::BinaryBytes => HumanBytes(value).to_string(),
+            Self::DecimalBytes => DecimalBytes(value).to_string(),
+        }
+    }
+
+    /// Formats an amount per second, without the `/s`. small rates keep one decimal place
+    #[must_use]
+    pub fn format_rate(self, rate: f64) -> String {
+        let scaled = match self {
+            Self::Plain => None,
+            // anything that would show as `1000.0` gets a separator too
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            Self::Thousands if rate >= 999.95 => Some(HumanCount(rate.round() as u64).to_string()),
+            Self::Thousands => None,
+            Self::Si => scaled(rate, 1000.0, SI_UNITS, ""),
+            Self::BinaryBytes => {
+                return scaled(rate, 1024.0, BINARY_UNITS, " ")
+                    .unwrap_or_else(|| format!("{rate:.1} B"))
+            }
+            Self::DecimalBytes => {
+                return scaled(rate, 1000.0, DECIMAL_UNITS, " ")
+                    .unwrap_or_else(|| format!("{rate:.1} B"))
+            }
+        };
+        scaled.unwrap_or_else(|| format!("{rate:.1}"))
+    }
+
+    /// If these units already say what is being counted (bytes), so no other unit should be shown with them
+    pub(crate) const fn is_bytes(self) -> bool {
+        matches!(self, Self::BinaryBytes | Self::DecimalBytes)
+    }
+}
@@ -0,0 +1,501 @@
+//! Readers and writers that show how many bytes have gone through them on a bar
+//!
+//! [`ProgressReader`] and [`ProgressWriter`] wrap anything that implements [`Read`] or [`Write`],
+//! setting the progress of a bar (that implements [`IteratorProgress`]) to the number of bytes read or written.
+//! they also implement [`BufRead`] and [`Seek`] when the type they wrap does.
+//!
+//! for the common case of copying everything from a reader to a writer, there is [`copy_with_progress`]
+//!
+//! with the `async` feature, there are also `AsyncProgressReader` and `AsyncProgressWriter` for tokio's `AsyncRead` and `AsyncWrite`
+//!
+//! ```rust
+//! use std::io::{Cursor, Read};
+//!
+//! use stati::bars::SimpleBar;
+//! use stati::io::ProgressReader;
+//! use stati::prelude::*;
+//! use stati::{BarManager, RenderMode};
+//!
+//! # fn main() -> std::io::Result<()> {
+//! let mut manager = BarManager::with_writer(std::io::sink()).render_mode(RenderMode::Plain);
+//! let bar = manager.register(SimpleBar::new("Reading", 0));
+//! // the length of the data is known, so it is used as the size hint
+//! let mut reader = ProgressReader::new(Cursor::new(vec![0u8; 1000]), bar).with_stream_len()?;
+//! let mut buf = [0; 300];
+//! reader.read_exact(&mut buf)?;
+//!
+//! let (_, mut bar) = reader.into_parts();
+//! assert_eq!(bar.bar().display_plain(), "Reading: 30% (300/1000)");
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
+
+use crate::subsets::IteratorProgress;
+use crate::wrapper::IsBarWrapper;
+
+/// Sets the progress of the bar in `wrapper`, doing nothing if it cannot be acquired
+pub(crate) fn set_progress<W>(wrapper: &mut W, progress: u64)
+where
+    W: IsBarWrapper,
+    W::Bar: IteratorProgress,
+{
+    if let Ok(mut bar) = wrapper.try_bar() {
+        bar.set_progress(usize::try_from(progress).unwrap_or(usize::MAX));
+    }
+}
+
+/// Like [`set_progress`], but for the size hint
+pub(crate) fn set_size_hint<W>(wrapper: &mut W, hint: u64)
+where
+    W: IsBarWrapper,
+    W::Bar: IteratorProgress,
+{
+    if let Ok(mut bar) = wrapper.try_bar() {
+        bar.set_size_hint(usize::try_from(hint).unwrap_or(usize::MAX));
+    }
+}
+
+/// Wraps a [`Read`]er, setting the progress of a bar to how many bytes have been read
+///
+/// after [seeking](Seek), the progress is the new position in the stream instead.
+/// when this is dropped, so is the bar wrapper, finishing the bar
+/// (use [`into_parts`](Self::into_parts) to keep it)
+#[derive(Debug)]
+pub struct ProgressReader<R, W> {
+    inner: R,
+    bar: W,
+    pos: u64,
+}
+
+impl<R, W> ProgressReader<R, W>
+where
+    W: IsBarWrapper,
+    W::Bar: IteratorProgress,
+{
+    /// Wraps `inner`, showing its progress on `bar`. the size hint of the bar is left alone
+    pub fn new(inner: R, bar: W) -> Self {
+        Self { inner, bar, pos: 0 }
+    }
+
+    /// Sets the size hint of the bar to `len` bytes
+    #[must_use]
+    pub fn with_len(mut self, len: u64) -> Self {
+        set_size_hint(&mut self.bar, len);
+        self
+    }
+
+    /// The wrapped reader
+    pub const fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// The wrapped reader. reading from it directly will not update the bar
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Takes the reader and bar wrapper back out
+    pub fn into_parts(self) -> (R, W) {
+        (self.inner, self.bar)
+    }
+
+    fn advance(&mut self, by: usize) {
+        self.pos += by as u64;
+        set_progress(&mut self.bar, self.pos);
+    }
+}
+
+impl<R, W> ProgressReader<R, W>
+where
+    R: Seek,
+    W: IsBarWrapper,
+    W::Bar: IteratorProgress,
+{
+    /// Sets the size hint of the bar to the length of the stream, and the progress to where it currently is in it
+    ///
+    /// # Errors
+    /// if the reader cannot be seeked to find its length
+    pub fn with_stream_len(mut self) -> io::Result<Self> {
+        let pos = self.inner.stream_position()?;
+        let len = self.inner.seek(SeekFrom::End(0))?;
+        self.inner.seek(SeekFrom::Start(pos))?;
+        self.pos = pos;
+        set_size_hint(&mut self.bar, len);
+        set_progress(&mut self.bar, pos);
+        Ok(self)
+    }
+}
+
+impl<R, W> Read for ProgressReader<R, W>
+where
+    R: Read,
+    W: IsBarWrapper,
+    W::Bar: IteratorProgress,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.advance(read);
+        Ok(read)
+    }
+}
+
+impl<R, W> BufRead for ProgressReader<R, W>
+where
+    R: BufRead,
+    W: IsBarWrapper,
+    W::Bar: IteratorProgress,
+{
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt);
+        self.advance(amt);
+    }
+}
+
+impl<R, W> Seek for ProgressReader<R, W>
+where
+    R: Seek,
+    W: IsBarWrapper,
+    W::Bar: IteratorProgress,
+{
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.pos = self.inner.seek(pos)?;
+        set_progress(&mut self.bar, self.pos);
+        Ok(self.pos)
+    }
+}
+
+/// Wraps a [`Write`]r, setting the progress of a bar to how many bytes have been written
+///
+/// after [seeking](Seek), the progress is the new position in the stream instead.
+/// when this is dropped, so is the bar wrapper, finishing the bar
+/// (use [`into_parts`](Self::into_parts) to keep it)
+#[derive(Debug)]
+pub struct ProgressWriter<Wr, W> {
+    inner: Wr,
+    bar: W,
+    pos: u64,
+}
+
+impl<Wr, W> ProgressWriter<Wr, W>
+where
+    W: IsBarWrapper,
+    W::Bar: IteratorProgress,
+{
+    /// Wraps `inner`, showing its progress on `bar`. the size hint of the bar is left alone
+    pub fn new(inner: Wr, bar: W) -> Self {
+        Self { inner, bar, pos: 0 }
+    }
+
+    /// Sets the size hint of the bar to `len` bytes, for when it is known how much will be written
+    #[must_use]
+    pub fn with_len(mut self, len: u64) -> Self {
+        set_size_hint(&mut self.bar, len);
+        self
+    }
+
+    /// The wrapped writer
+    pub const fn get_ref(&self) -> &Wr {
+        &self.inner
+    }
+
+    /// The wrapped writer. writing to it directly will not update the bar
+    pub fn get_mut(&mut self) -> &mut Wr {
+        &mut self.inner
+    }
+
+    /// Takes the writer and bar wrapper back out
+    pub fn into_parts(self) -> (Wr, W) {
+        (self.inner, self.bar)
+    }
+}
+
+impl<Wr, W> Write for ProgressWriter<Wr, W>
+where
+    Wr: Write,
+    W: IsBarWrapper,
+    W::Bar: IteratorProgress,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.pos += written as u64;
+        set_progress(&mut self.bar, self.pos);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<Wr, W> Seek for ProgressWriter<Wr, W>
+where
+    Wr: Seek,
+    W: IsBarWrapper,
+    W::Bar: IteratorProgress,
+{
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.pos = self.inner.seek(pos)?;
+        set_progress(&mut self.bar, self.pos);
+        Ok(self.pos)
+    }
+}
+
+/// Copies everything from `reader` to `writer` (like [`io::copy`]), showing how many bytes have been copied on `bar`.
+///
+/// the size hint of the bar is left alone, so set it first if the length is known.
+/// the bar is finished once everything has been copied, as the wrapper is dropped.
+/// returns how many bytes were copied
+///
+/// ```rust
+/// use stati::bars::SimpleBar;
+/// use stati::prelude::*;
+/// use stati::{BarManager, RenderMode};
+///
+/// # fn main() -> std::io::Result<()> {
+/// let mut out = Vec::new();
+/// {
+///     let mut manager = BarManager::with_writer(&mut out).render_mode(RenderMode::Plain);
+///     let bar = manager.register(SimpleBar::new("Copying", 64));
+///     let mut copy = Vec::new();
+///     let copied = stati::copy_with_progress(&mut &[7u8; 64][..], &mut copy, bar)?;
+///     assert_eq!(copied, 64);
+///     assert_eq!(copy, [7; 64]);
+///     manager.print();
+/// }
+/// assert_eq!(String::from_utf8(out).unwrap(), "Copying: 100% (64/64)\n");
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+/// if reading or writing fails, like [`io::copy`]
+pub fn copy_with_progress<R, Wr, W>(reader: &mut R, writer: &mut Wr, bar: W) -> io::Result<u64>
+where
+    R: Read + ?Sized,
+    Wr: Write + ?Sized,
+    W: IsBarWrapper,
+    W::Bar: IteratorProgress,
+{
+    io::copy(&mut ProgressReader::new(reader, bar), writer)
+}
+
+#[cfg(feature = "async")]
+pub use self::asynchronous::{AsyncProgressReader, AsyncProgressWriter};
+
+#[cfg(feature = "async")]
+mod asynchronous {
+    use std::io;
+    use std::pin::Pin;
+    use std::task::{ready, Context, Poll};
+
+    use tokio::io::{AsyncBufRead, AsyncRead, AsyncWrite, ReadBuf};
+
+    use super::{set_progress, set_size_hint};
+    use crate::subsets::IteratorProgress;
+    use crate::wrapper::IsBarWrapper;
+
+    pin_project_lite::pin_project! {
+        /// Like [`ProgressReader`](super::ProgressReader), but for tokio's [`AsyncRead`]
+        ///
+        /// ```rust
+        /// use tokio::io::AsyncReadExt;
+        ///
+        /// use stati::bars::SimpleBar;
+        /// use stati::io::AsyncProgressReader;
+        /// use stati::prelude::*;
+        /// use stati::{BarManager, RenderMode};
+        ///
+        /// # fn main() {
+        /// let mut manager = BarManager::with_writer(std::io::sink()).render_mode(RenderMode::Plain);
+        /// let bar = manager.register_threadsafe(SimpleBar::new("Reading", 0));
+        /// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        /// runtime.block_on(async {
+        ///     let mut reader = AsyncProgressReader::new(&[0u8; 1000][..], bar).with_len(1000);
+        ///     let mut buf = [0; 300];
+        ///     reader.read_exact(&mut buf).await.unwrap();
+        ///
+        ///     let (_, mut bar) = reader.into_parts();
+        ///     assert_eq!(bar.bar().display_plain(), "Reading: 30% (300/1000)");
+        /// });
+        /// # }
+        /// ```
+        #[derive(Debug)]
+        pub struct AsyncProgressReader<R, W> {
+            #[pin]
+            inner: R,
+            bar: W,
+            pos: u64,
+        }
+    }
+
+    impl<R, W> AsyncProgressReader<R, W>
+    where
+        W: IsBarWrapper,
+        W::Bar: IteratorProgress,
+    {
+        /// Wraps `inner`, showing its progress on `bar`. the size hint of the bar is left alone
+        pub fn new(inner: R, bar: W) -> Self {
+            Self { inner, bar, pos: 0 }
+        }
+
+        /// Sets the size hint of the bar to `len` bytes
+        #[must_use]
+        pub fn with_len(mut self, len: u64) -> Self {
+            set_size_hint(&mut self.bar, len);
+            self
+        }
+
+        /// The wrapped reader
+        pub const fn get_ref(&self) -> &R {
+            &self.inner
+        }
+
+        /// The wrapped reader. reading from it directly will not update the bar
+        pub fn get_mut(&mut self) -> &mut R {
+            &mut self.inner
+        }
+
+        /// Takes the reader and bar wrapper back out
+        pub fn into_parts(self) -> (R, W) {
+            (self.inner, self.bar)
+        }
+    }
+
+    impl<R, W> AsyncRead for AsyncProgressReader<R, W>
+    where
+        R: AsyncRead,
+        W: IsBarWrapper,
+        W::Bar: IteratorProgress,
+    {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            let this = self.project();
+            let before = buf.filled().len();
+            ready!(this.inner.poll_read(cx, buf))?;
+            *this.pos += (buf.filled().len() - before) as u64;
+            set_progress(this.bar, *this.pos);
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    impl<R, W> AsyncBufRead for AsyncProgressReader<R, W>
+    where
+        R: AsyncBufRead,
+        W: IsBarWrapper,
+        W::Bar: IteratorProgress,
+    {
+        fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+            self.project().inner.poll_fill_buf(cx)
+        }
+
+        fn consume(self: Pin<&mut Self>, amt: usize) {
+            let this = self.project();
+            this.inner.consume(amt);
+            *this.pos += amt as u64;
+            set_progress(this.bar, *this.pos);
+        }
+    }
+
+    pin_project_lite::pin_project! {
+        /// Like [`ProgressWriter`](super::ProgressWriter), but for tokio's [`AsyncWrite`]
+        ///
+        /// ```rust
+        /// use tokio::io::AsyncWriteExt;
+        ///
+        /// use stati::bars::SimpleBar;
+        /// use stati::io::AsyncProgressWriter;
+        /// use stati::prelude::*;
+        /// use stati::{BarManager, RenderMode};
+        ///
+        /// # fn main() {
+        /// let mut manager = BarManager::with_writer(std::io::sink()).render_mode(RenderMode::Plain);
+        /// let bar = manager.register_threadsafe(SimpleBar::new("Writing", 0));
+        /// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        /// runtime.block_on(async {
+        ///     let mut writer = AsyncProgressWriter::new(Vec::new(), bar).with_len(10);
+        ///     writer.write_all(b"hello").await.unwrap();
+        ///
+        ///     let (written, mut bar) = writer.into_parts();
+        ///     assert_eq!(written, b"hello");
+        ///     assert_eq!(bar.bar().display_plain(), "Writing: 50% (5/10)");
+        /// });
+        /// # }
+        /// ```
+        #[derive(Debug)]
+        pub struct AsyncProgressWriter<Wr, W> {
+            #[pin]
+            inner: Wr,
+            bar: W,
+            pos: u64,
+        }
+    }
+
+    impl<Wr, W> AsyncProgressWriter<Wr, W>
+    where
+        W: IsBarWrapper,
+        W::Bar: IteratorProgress,
+    {
+        /// Wraps `inner`, showing its progress on `bar`. the size hint of the bar is left alone
+        pub fn new(inner: Wr, bar: W) -> Self {
+            Self { inner, bar, pos: 0 }
+        }
+
+        /// Sets the size hint of the bar to `len` bytes, for when it is known how much will be written
+        #[must_use]
+        pub fn with_len(mut self, len: u64) -> Self {
+            set_size_hint(&mut self.bar, len);
+            self
+        }
+
+        /// The wrapped writer
+        pub const fn get_ref(&self) -> &Wr {
+            &self.inner
+        }
+
+        /// The wrapped writer. writing to it directly will not update the bar
+        pub fn get_mut(&mut self) -> &mut Wr {
+            &mut self.inner
+        }
+
+        /// Takes the writer and bar wrapper back out
+        pub fn into_parts(self) -> (Wr, W) {
+            (self.inner, self.bar)
+        }
+    }
+
+    impl<Wr, W> AsyncWrite for AsyncProgressWriter<Wr, W>
+    where
+        Wr: AsyncWrite,
+        W: IsBarWrapper,
+        W::Bar: IteratorProgress,
+    {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            let this = self.project();
+            let written = ready!(this.inner.poll_write(cx, buf))?;
+            *this.pos += written as u64;
+            set_progress(this.bar, *this.pos);
+            Poll::Ready(Ok(written))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            self.project().inner.poll_flush(cx)
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            self.project().inner.poll_shutdown(cx)
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::fmt::Debug;
 
-use super::{BarCloseMethod, IsBar};
+use super::{BarCloseMethod, FinishState, IsBar, RenderContext};
 
 /// Internal interface for a progress bar, which is held by [`BarManager`]
 ///
@@ -9,19 +9,37 @@ use super::{BarCloseMethod, IsBar};
 /// [`BarManager`]: crate::manager::BarManager
 /// [`IsBar`]: crate::isbar::IsBar
 pub trait IsBarManagerInterface: Debug {
-    fn display(&mut self) -> String;
+    fn done(&mut self);
+
+    fn display_with(&mut self, ctx: &RenderContext) -> String;
+
+    fn display_plain(&mut self) -> String;
 
     fn is_done(&self) -> bool;
 
     fn close_method(&self) -> BarCloseMethod;
+
+    fn finish_state(&self) -> FinishState;
+
+    fn fraction(&self) -> Option<f64>;
+
+    fn sync(&mut self);
 }
 
 impl<T> IsBarManagerInterface for T
 where
     T: IsBar + Debug,
 {
-    fn display(&mut self) -> String {
-        <T as IsBar>::display(self)
+    fn done(&mut self) {
+        <T as IsBar>::done(self);
+    }
+
+    fn display_with(&mut self, ctx: &RenderContext) -> String {
+        <T as IsBar>::display_with(self, ctx)
+    }
+
+    fn display_plain(&mut self) -> String {
+        <T as IsBar>::display_plain(self)
     }
 
     fn is_done(&self) -> bool {
@@ -31,4 +49,16 @@ where
     fn close_method(&self) -> BarCloseMethod {
         self.close_method()
     }
+
+    fn finish_state(&self) -> FinishState {
+        <T as IsBar>::finish_state(self)
+    }
+
+    fn fraction(&self) -> Option<f64> {
+        <T as IsBar>::fraction(self)
+    }
+
+    fn sync(&mut self) {
+        <T as IsBar>::sync(self);
+    }
 }
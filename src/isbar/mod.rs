@@ -3,18 +3,86 @@ pub mod subsets;
 
 pub use manager_interface::IsBarManagerInterface;
 
+use crate::style::ColorSupport;
+
 /// How the bar is handled when it is completed ([`done`] is called)
 ///
 /// [`done`]: IsBar::done
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum BarCloseMethod {
     /// Print the bar one last time, then cease tracking it.
-    /// The bar will be moved after all currently tracked bars.
+    /// The bar will be moved above all currently tracked bars.
     LeaveBehind,
     /// Delete the bar, clearing it from the screen.
     Clear,
 }
 
+/// How a bar ended, returned by [`IsBar::finish_state`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum FinishState {
+    /// Finished with [`done`](IsBar::done), without saying if it worked or not
+    Done,
+    /// The job completed successfully
+    Success,
+    /// The job failed
+    Error,
+    /// The job was stopped before it could complete
+    Abandoned,
+}
+
+/// Information about where a bar is being drawn, passed to [`IsBar::display_with`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub struct RenderContext {
+    /// The number of columns the bar has to fit in
+    pub width: usize,
+    /// If the bar can use colors and other text styles (see [`style`](crate::style))
+    pub color: bool,
+    /// How many colors the terminal can show. colors are converted to the closest ones it supports
+    pub color_support: ColorSupport,
+}
+
+impl RenderContext {
+    /// Creates a new [`RenderContext`] for a bar that has to fit in `width` columns, without colors
+    #[must_use]
+    pub const fn new(width: usize) -> Self {
+        Self {
+            width,
+            color: false,
+            color_support: ColorSupport::Basic,
+        }
+    }
+
+    /// Sets if the bar can use colors
+    #[must_use]
+    pub const fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets how many colors the terminal can show
+    #[must_use]
+    pub const fn with_color_support(mut self, support: ColorSupport) -> Self {
+        self.color_support = support;
+        self
+    }
+
+    /// Creates a new [`RenderContext`] for drawing to stdout.
+    ///
+    /// if it cannot get the real terminal size, uses 81 as the width.
+    /// colors are used if stdout is a terminal, and the environment allows them (see [`colors_allowed`]).
+    /// the number of colors supported is guessed with [`ColorSupport::detect`]
+    ///
+    /// [`colors_allowed`]: crate::style::colors_allowed
+    #[must_use]
+    pub fn detect() -> Self {
+        use std::io::IsTerminal;
+        Self::new(crate::utils::term_width().map_or(crate::utils::DEFAULT_WIDTH, usize::from))
+            .with_color(std::io::stdout().is_terminal() && crate::style::colors_allowed())
+            .with_color_support(ColorSupport::detect())
+    }
+}
+
 /// Basic API of a progress bar,
 /// providing methods required for all progress bars
 ///
@@ -36,10 +104,72 @@ pub trait IsBar {
 
     /// Formats the [`Bar`] into a string. this is generaly only used by the [`BarManager`]
     ///
+    /// the string can have more than one line, and lines can be wider than the terminal.
+    /// the [`BarManager`] counts how many rows they take up after wrapping, so they are still cleared properly
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use stati::{BarCloseMethod, BarManager, IsBar};
+    ///
+    /// #[derive(Debug)]
+    /// struct TwoLines;
+    ///
+    /// impl IsBar for TwoLines {
+    ///     fn done(&mut self) {}
+    ///     fn is_done(&self) -> bool { false }
+    ///     fn close_method(&self) -> BarCloseMethod { BarCloseMethod::Clear }
+    ///     fn display(&mut self) -> String {
+    ///         // the second line is too long for the terminal, so it wraps onto a third row
+    ///         format!("first line\n{}", "=".repeat(100))
+    ///     }
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut out = Vec::new();
+    /// {
+    ///     // output that is not a terminal is treated as 81 columns wide
+    ///     let mut manager = BarManager::with_writer(&mut out).min_redraw_interval(Duration::ZERO);
+    ///     let _bar = manager.register(TwoLines);
+    ///     manager.print();
+    ///     manager.print();
+    /// }
+    /// // the second draw moves the cursor back up over all 3 rows
+    /// assert!(String::from_utf8(out).unwrap().contains("\x1b[3F"));
+    /// # }
+    /// ```
+    ///
     /// [`Bar`]: IsBar
     /// [`BarManager`]: crate::manager::BarManager
     fn display(&mut self) -> String;
 
+    /// Like [`display`], but fitting the [`Bar`] into the space described by `ctx` instead of
+    /// the whole terminal. the [`BarManager`] uses this to make room for indenting child bars,
+    /// and to use the size of the terminal it is actually writing to.
+    ///
+    /// by default this ignores `ctx` and calls [`display`]
+    ///
+    /// [`Bar`]: IsBar
+    /// [`BarManager`]: crate::manager::BarManager
+    /// [`display`]: IsBar::display
+    fn display_with(&mut self, ctx: &RenderContext) -> String {
+        let _ = ctx;
+        self.display()
+    }
+
+    /// Formats the [`Bar`] into a single line of plain text, without any ANSI escape codes or carriage returns.
+    ///
+    /// this is used by the [`BarManager`] when its output is not a terminal (see [`RenderMode::Plain`]),
+    /// and by default strips those characters out of [`display`]
+    ///
+    /// [`Bar`]: IsBar
+    /// [`BarManager`]: crate::manager::BarManager
+    /// [`RenderMode::Plain`]: crate::manager::RenderMode::Plain
+    /// [`display`]: IsBar::display
+    fn display_plain(&mut self) -> String {
+        crate::utils::strip_ansi(&self.display())
+    }
+
     /// Returns how the bar should be handled by the [`BarManager`] after [`done`] is called
     ///
     /// this is for internal use
@@ -47,4 +177,39 @@ pub trait IsBar {
     /// [`done`]: IsBar::done
     /// [`BarManager`]: crate::manager::BarManager
     fn close_method(&self) -> BarCloseMethod;
+
+    /// How the [`Bar`] ended. only meaningful once it [is done](IsBar::is_done)
+    ///
+    /// this is used by the [`BarManager`] to count how many bars ended in each state,
+    /// and returns [`FinishState::Done`] by default
+    ///
+    /// [`Bar`]: IsBar
+    /// [`BarManager`]: crate::manager::BarManager
+    fn finish_state(&self) -> FinishState {
+        FinishState::Done
+    }
+
+    /// How far along the [`Bar`] is, from `0.0` to `1.0`, if it knows.
+    ///
+    /// this is used by the [`BarManager`] to work out the progress of parent bars from their children,
+    /// and returns `None` by default
+    ///
+    /// [`Bar`]: IsBar
+    /// [`BarManager`]: crate::manager::BarManager
+    fn fraction(&self) -> Option<f64> {
+        None
+    }
+
+    /// Applies changes made through a [`ProgressCounter`] attached to the [`Bar`], like [`ProgressState::sync`].
+    ///
+    /// bars already do this before they are drawn, but the [`BarManager`] also calls it before reading [`fraction`],
+    /// so a parent's progress does not lag behind children that are only updated through their counter.
+    /// does nothing by default
+    ///
+    /// [`Bar`]: IsBar
+    /// [`BarManager`]: crate::manager::BarManager
+    /// [`ProgressCounter`]: crate::ProgressCounter
+    /// [`ProgressState::sync`]: crate::ProgressState::sync
+    /// [`fraction`]: IsBar::fraction
+    fn sync(&mut self) {}
 }
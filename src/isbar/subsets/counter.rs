@@ -0,0 +1,12 @@
+use crate::ProgressCounter;
+
+/// API for bars that can be updated through a [`ProgressCounter`], without locking them.
+/// used by the parallel iterator wrapper in `par_iter` (with the `rayon` feature)
+///
+/// the bar has to apply changes made through the counter itself, before it is drawn
+/// (see [`ProgressState::sync`](crate::ProgressState::sync))
+pub trait CounterProgress {
+    /// A handle for updating the bar from any thread, see [`counter`](crate::counter).
+    /// every call returns a clone of the same counter
+    fn counter(&mut self) -> ProgressCounter;
+}
@@ -3,8 +3,11 @@ Traits for subsets of progress bars, that extend the API to support more feature
 
 Current subsets:
  - IteratorProgress: general api for bars that can be used with the iterator wrapper
+ - CounterProgress: bars that can be updated without locking them, used by the parallel iterator wrapper
 */
 // ! note: ADD ALL TRAITS HERE TO THE PRELUDE
+mod counter;
 mod iter;
 
+pub use counter::CounterProgress;
 pub use iter::IteratorProgress;
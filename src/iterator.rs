@@ -46,10 +46,14 @@ where
             .set_size_hint(if let Some(hint) = self.manual_hint {
                 hint
             } else {
-                std::cmp::max(
-                    self.items_count,
-                    self.items_count + std::cmp::max(lower, upper.unwrap_or(0)),
-                )
+                // with no upper bound, the lower one is the best guess there is,
+                // and if that is 0 too, the length is unknown (0)
+                let hint = upper.map_or(lower, |upper| std::cmp::max(lower, upper));
+                if hint == 0 && upper.is_none() {
+                    0
+                } else {
+                    self.items_count + hint
+                }
             });
 
         if let Some(i) = next {
@@ -82,3 +86,37 @@ pub trait ProgressTrackingAdaptor<T>: Iterator<Item = T> + Sized {
 }
 
 impl<T, I: Iterator<Item = T>> ProgressTrackingAdaptor<T> for I {}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::ProgressTrackingAdaptor;
+    use crate::bars::{BundledBar, SimpleBar};
+    use crate::manager::BarId;
+    use crate::wrapper::{BarWrapper, RedrawSignal};
+
+    fn len_after_first(iter: impl Iterator<Item = u32>) -> Option<usize> {
+        let bar = Rc::new(RefCell::new(SimpleBar::new("test", 0)));
+        let wrapper = BarWrapper::new(Rc::clone(&bar), BarId::next(), RedrawSignal::default());
+        iter.display_bar(wrapper).next();
+        let len = bar.borrow().state().len();
+        len
+    }
+
+    #[test]
+    fn lower_bound_is_used_without_an_upper_one() {
+        let unbounded = || std::iter::repeat(0).take_while(|_| true);
+        // `take_while` has neither bound, so the length is unknown
+        assert_eq!(len_after_first(unbounded()), None);
+        // `chain` with an unbounded iterator has no upper bound, but the `0..10` part still gives a lower one
+        assert_eq!(len_after_first((0..10).chain(unbounded())), Some(9));
+    }
+
+    #[test]
+    fn upper_bound_is_used_when_the_lower_one_is_zero() {
+        // `filter` could drop every item, so its lower bound is 0 while the upper one is the whole range
+        assert_eq!(len_after_first((0..10).filter(|_| true)), Some(9));
+    }
+}
@@ -5,26 +5,54 @@
 //!   - enables using `parking_lot`'s `FairMutex`
 //!   - (adds some overhead but may fix some issues?)
 //! - nightly:
-//!   - enables using nighlty rust (generic_associated_types) for some extra optimizations
+//!   - makes [`IsBarWrapper::try_bar`](wrapper::IsBarWrapper::try_bar) return the lock guard directly, using generic associated types, instead of boxing it
+//!   - generic associated types are stable since rust 1.65, so this no longer needs a nightly compiler
+//! - async:
+//!   - adds progress bars for tokio's `AsyncRead` and `AsyncWrite` (see [`io`]), and for `Stream`s (see the `stream` module)
+//! - rayon:
+//!   - adds progress bars for rayon's parallel iterators (see the `par_iter` module)
 //!
 
-#![cfg_attr(feature = "nightly", feature(generic_associated_types))]
-
 extern crate parking_lot;
 extern crate terminal_size;
+extern crate unicode_segmentation;
+extern crate unicode_width;
 
 pub mod bars;
+pub mod clock;
+pub mod counter;
+pub mod format;
+pub mod io;
 pub(crate) mod isbar;
 pub mod iterator;
 pub mod macros;
 pub(crate) mod manager;
+#[cfg(feature = "rayon")]
+pub mod par_iter;
 pub mod prelude;
 pub(crate) mod sealant;
+pub mod state;
+#[cfg(feature = "async")]
+pub mod stream;
+pub mod style;
 pub(crate) mod utils;
 pub mod wrapper;
 
+pub use counter::ProgressCounter;
+pub use io::copy_with_progress;
 pub use isbar::subsets;
 pub use isbar::BarCloseMethod;
+pub use isbar::FinishState;
 pub use isbar::IsBar;
+pub use isbar::RenderContext;
+pub use manager::Anchor;
+pub use manager::BarId;
 pub use manager::BarManager;
+pub use manager::FinishCounts;
+pub use manager::LocalBarsError;
+pub use manager::ManagedBar;
+pub use manager::Placement;
+pub use manager::RenderHandle;
+pub use manager::RenderMode;
+pub use state::ProgressState;
 pub use wrapper::ThreadedBarWrapper;
@@ -0,0 +1,259 @@
+use core::cell::RefCell;
+use std::fmt::Debug;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "fairness")]
+use parking_lot::FairMutex as Mutex;
+#[cfg(not(feature = "fairness"))]
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+use crate::isbar::IsBarManagerInterface;
+use crate::subsets::IteratorProgress;
+use crate::wrapper::RedrawSignal;
+use crate::BarCloseMethod;
+
+/// Unique identifier for a bar registered with a [`BarManager`]
+///
+/// ids are never reused, even across different managers,
+/// so an id for a bar that has finished will never refer to a different bar
+///
+/// [`BarManager`]: super::BarManager
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct BarId(u64);
+
+impl BarId {
+    /// Creates a new, unique id
+    pub(crate) fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// A bar tracked by the [`BarManager`], which may or may not be threadsafe
+///
+/// [`BarManager`]: super::BarManager
+#[derive(Debug)]
+pub(super) enum BarRef<'bar> {
+    Local(Rc<RefCell<dyn IsBarManagerInterface + 'bar>>),
+    Threaded(Arc<Mutex<dyn IsBarManagerInterface + Send + 'bar>>),
+}
+
+impl<'bar> BarRef<'bar> {
+    /// Runs `f` on the bar
+    ///
+    /// # Panics
+    /// if the bar is single threaded, and is already borrowed
+    pub(super) fn with<R>(&self, f: impl FnOnce(&mut dyn IsBarManagerInterface) -> R) -> R {
+        match self {
+            Self::Local(bar) => f(&mut *bar.borrow_mut()),
+            Self::Threaded(bar) => f(&mut *bar.lock()),
+        }
+    }
+}
+
+/// A bar whose progress is set by the [`BarManager`] from the progress of its children
+///
+/// [`BarManager`]: super::BarManager
+pub(super) enum ProgressRef<'bar> {
+    Local(Rc<RefCell<dyn IteratorProgress + 'bar>>),
+    Threaded(Arc<Mutex<dyn IteratorProgress + Send + 'bar>>),
+}
+
+impl<'bar> ProgressRef<'bar> {
+    /// Runs `f` on the bar
+    ///
+    /// # Panics
+    /// if the bar is single threaded, and is already borrowed
+    pub(super) fn with<R>(&self, f: impl FnOnce(&mut dyn IteratorProgress) -> R) -> R {
+        match self {
+            Self::Local(bar) => f(&mut *bar.borrow_mut()),
+            Self::Threaded(bar) => f(&mut *bar.lock()),
+        }
+    }
+}
+
+impl Debug for ProgressRef<'_> {
+    // the bar itself is already shown by the entry's `BarRef`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Local(_) => f.write_str("Local"),
+            Self::Threaded(_) => f.write_str("Threaded"),
+        }
+    }
+}
+
+/// Which group of bars a [`ManagedBar`] is displayed in. bars are always kept sorted by this
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub(super) enum Section {
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// A bar tracked by a [`BarManager`], as returned by [`get`] and [`iter`]
+///
+/// this allows inspecting and finishing bars without the wrapper that was returned when they were registered
+///
+/// # Panics
+/// methods that access the bar panic if it was registered with [`register`],
+/// and the [`BarWrapper`] for it is currently borrowing it.
+/// bars registered with [`register_threadsafe`] will block until they are available instead
+///
+/// [`BarManager`]: super::BarManager
+/// [`get`]: super::BarManager::get
+/// [`iter`]: super::BarManager::iter
+/// [`register`]: super::BarManager::register
+/// [`register_threadsafe`]: super::BarManager::register_threadsafe
+/// [`BarWrapper`]: crate::wrapper::BarWrapper
+#[derive(Debug)]
+pub struct ManagedBar<'bar> {
+    pub(super) node: Node,
+    pub(super) bar: BarRef<'bar>,
+    /// set when the bar's progress is worked out from its children
+    pub(super) aggregate: Option<ProgressRef<'bar>>,
+    /// the manager's, so finishing the bar through this is drawn right away
+    pub(super) signal: RedrawSignal,
+}
+
+/// Where a [`ManagedBar`] sits among the other bars, and everything else the manager tracks for it
+///
+/// bars are kept in the order they are displayed, with each bar's children directly after it
+#[derive(Clone, Copy, Debug)]
+pub(super) struct Node {
+    pub(super) id: BarId,
+    pub(super) section: Section,
+    pub(super) parent: Option<BarId>,
+    /// how many parents the bar has above it
+    pub(super) depth: usize,
+    /// how much the bar counts towards its parent's progress, if the parent is aggregated
+    pub(super) weight: f64,
+    /// the bar is done and has already been handled according to its close method,
+    /// but is being kept around as its parent is not finished yet
+    pub(super) finished: bool,
+}
+
+impl Node {
+    pub(super) const fn new(
+        id: BarId,
+        section: Section,
+        parent: Option<BarId>,
+        depth: usize,
+    ) -> Self {
+        Self {
+            id,
+            section,
+            parent,
+            depth,
+            weight: 1.0,
+            finished: false,
+        }
+    }
+}
+
+impl<'bar> ManagedBar<'bar> {
+    pub(super) const fn new(node: Node, bar: BarRef<'bar>, signal: RedrawSignal) -> Self {
+        Self {
+            node,
+            bar,
+            aggregate: None,
+            signal,
+        }
+    }
+
+    /// The id of the bar
+    #[must_use]
+    pub const fn id(&self) -> BarId {
+        self.node.id
+    }
+
+    /// The id of the bar's parent, if it was registered as a child of another bar
+    #[must_use]
+    pub const fn parent(&self) -> Option<BarId> {
+        self.node.parent
+    }
+
+    /// Checks if the bar was registered with [`register_threadsafe`](super::BarManager::register_threadsafe)
+    #[must_use]
+    pub const fn is_threadsafe(&self) -> bool {
+        matches!(self.bar, BarRef::Threaded(_))
+    }
+
+    /// Finishes the bar, the same as [`IsBar::done`](crate::IsBar::done)
+    pub fn done(&self) {
+        if self.bar.with(|bar| {
+            let was_done = bar.is_done();
+            bar.done();
+            !was_done
+        }) {
+            self.signal.notify();
+        }
+    }
+
+    /// Checks if the bar is done, the same as [`IsBar::is_done`](crate::IsBar::is_done)
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        self.bar.with(|bar| bar.is_done())
+    }
+
+    /// How the bar will be handled once it is done, the same as [`IsBar::close_method`](crate::IsBar::close_method)
+    #[must_use]
+    pub fn close_method(&self) -> BarCloseMethod {
+        self.bar.with(|bar| bar.close_method())
+    }
+
+    /// Formats the bar as plain text, the same as [`IsBar::display_plain`](crate::IsBar::display_plain)
+    #[must_use]
+    pub fn display_plain(&self) -> String {
+        self.bar.with(|bar| bar.display_plain())
+    }
+}
+
+/// Something that refers to a bar tracked by a [`BarManager`], so other bars can be placed relative to it
+///
+/// this is implemented for [`BarId`], [`BarWrapper`] and [`ThreadedBarWrapper`]
+///
+/// [`BarManager`]: super::BarManager
+/// [`BarWrapper`]: crate::wrapper::BarWrapper
+/// [`ThreadedBarWrapper`]: crate::wrapper::ThreadedBarWrapper
+pub trait Anchor: crate::sealant::Sealed + Debug {
+    /// The id of the bar this refers to
+    fn bar_id(&self) -> BarId;
+}
+
+impl Anchor for BarId {
+    fn bar_id(&self) -> BarId {
+        *self
+    }
+}
+
+impl crate::sealant::Sealed for BarId {}
+
+/// Where to put a new bar, for [`register_at`] and [`register_threadsafe_at`]
+///
+/// if the bar given to [`Before`] or [`After`] is no longer tracked by the manager,
+/// the new bar is placed at the [`End`] instead
+///
+/// [`register_at`]: super::BarManager::register_at
+/// [`register_threadsafe_at`]: super::BarManager::register_threadsafe_at
+/// [`Before`]: Placement::Before
+/// [`After`]: Placement::After
+/// [`End`]: Placement::End
+#[derive(Clone, Copy, Debug)]
+pub enum Placement<'a> {
+    /// After all other bars, apart from ones pinned to the bottom. this is where [`register`] puts bars
+    ///
+    /// [`register`]: super::BarManager::register
+    End,
+    /// Directly above another bar
+    Before(&'a dyn Anchor),
+    /// Directly below another bar
+    After(&'a dyn Anchor),
+    /// Pinned above all other bars, staying there as more are added.
+    /// bars pinned to the top earlier stay above it
+    PinTop,
+    /// Pinned below all other bars, staying there as more are added.
+    /// bars pinned to the bottom earlier stay above it
+    PinBottom,
+}
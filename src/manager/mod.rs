@@ -0,0 +1,1278 @@
+use core::cell::RefCell;
+use std::fmt::{self, Debug};
+use std::io::{IsTerminal, Write};
+use std::rc::Rc;
+
+#[cfg(feature = "fairness")]
+use parking_lot::FairMutex as Mutex;
+#[cfg(not(feature = "fairness"))]
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::isbar::{IsBar, RenderContext};
+use crate::style::ColorSupport;
+use crate::subsets::IteratorProgress;
+use crate::utils::DEFAULT_WIDTH;
+use crate::wrapper::{BarWrapper, RedrawSignal, ThreadedBarWrapper};
+use crate::{BarCloseMethod, FinishState};
+
+mod entry;
+mod renderer;
+
+use entry::{BarRef, Node, ProgressRef, Section};
+
+pub use entry::{Anchor, BarId, ManagedBar, Placement};
+pub use renderer::{LocalBarsError, RenderHandle};
+
+/**
+Manager for all current progress bars and text output.
+
+the bars produced by this can be used in other threads from the manager, if they are created with [`register_threadsafe`].
+to keep those bars drawing while the thread that owns the manager is busy, see [`spawn_renderer`]
+
+This can be used, with the [`register`] and [`register_threadsafe`] methods and
+the [`println!`] and [`print!`] (crate) macros
+to display progress bars and even print while doing so
+
+to display the bar, simply call `.print()`.
+in adition, the bar will be automaticaly printed when using
+the [`print!`] and [`println!`] macros.
+
+to keep tight loops fast, the bars are redrawn at most once every 50ms by default,
+with calls to `.print()` in between only marking them as needing a redraw.
+queued text, new bars and finished bars are always drawn right away,
+and anything still waiting (including bars that finished after the last call) is drawn when the manager is dropped.
+see [`min_redraw_interval`] to change this.
+
+## Examples
+
+simple bar
+
+```rust
+use std::thread;
+
+use stati::BarManager;
+use stati::prelude::*;
+
+# fn main() {
+let mut manager = BarManager::new();
+let mut bar = manager.register(stati::bars::SimpleBar::new(&"Working...", 100));
+for i in 0..=100 {
+    bar.bar().set_progress(i);
+    manager.print();
+    # #[allow(deprecated)]
+    thread::sleep_ms(40);
+}
+# }
+```
+
+printing while using progress bar
+
+```rust
+use std::thread;
+
+use stati::BarManager;
+use stati::prelude::*;
+
+# fn main() {
+let mut manager = BarManager::new();
+let mut bar = manager.register(stati::bars::SimpleBar::new(&"Working...", 100));
+for i in 0..=100 {
+    bar.bar().set_progress(i);
+    stati::println!(manager, "Progressed to {} in the first section", i);
+    manager.print();
+    # #[allow(deprecated)]
+    thread::sleep_ms(40);
+}
+# }
+```
+
+writing somewhere other than stdout
+
+```rust
+use stati::BarManager;
+use stati::prelude::*;
+
+# fn main() {
+let mut out = Vec::new();
+{
+    let mut manager = BarManager::with_writer(&mut out);
+    let mut bar = manager.register(stati::bars::SimpleBar::new(&"Working...", 100));
+    bar.bar().set_progress(50);
+    manager.print();
+}
+assert!(String::from_utf8(out).unwrap().contains("Working..."));
+# }
+```
+
+# Output that is not a terminal
+when the output is not a terminal (CI logs, files, piping through `tee`), redrawing bars in place does not work.
+in this case, the manager switches to [`RenderMode::Plain`], printing a plain status line for each bar
+every so often (see [`plain_interval`]) and when it finishes, with printed text passed through unchanged.
+
+this is detected automatically for stdout and stderr, but can be overridden with [`render_mode`],
+or by setting the `STATI_MODE` environment variable to `plain` or `interactive`.
+managers created with [`with_writer`] are interactive unless told otherwise.
+
+```rust
+use stati::{BarManager, RenderMode};
+use stati::prelude::*;
+
+# fn main() {
+let mut out = Vec::new();
+{
+    let mut manager = BarManager::with_writer(&mut out).render_mode(RenderMode::Plain);
+    let mut bar = manager.register(stati::bars::SimpleBar::new(&"Working...", 100));
+    bar.bar().set_progress(50);
+    manager.print();
+    stati::println!(manager, "halfway there");
+    bar.bar().set_progress(100);
+    drop(bar);
+}
+assert_eq!(
+    String::from_utf8(out).unwrap(),
+    "Working...: 50% (50/100)\nhalfway there\nWorking...: 100% (100/100)\n"
+);
+# }
+```
+
+# A note on ANSI controll charecters
+bars can be colored (see [`style`](crate::style)). escape codes that only change the style of text
+take up no space when working out how wide a line is, but other escape codes (like moving the cursor)
+will confuse the manager when it redraws bars.
+
+colors are turned off when the output is not a terminal, or the environment asks for no colors
+(see [`colors_allowed`](crate::style::colors_allowed)). to change this, use [`colors`]
+
+# a note on bar ordering
+bars are displayed in the order they were registered, whether they are threadsafe or not.
+to put a bar somewhere else, like above another bar or pinned to the top, use [`register_at`] or [`register_threadsafe_at`]
+
+```rust
+use stati::{BarManager, Placement, RenderMode};
+
+# fn main() {
+let mut out = Vec::new();
+{
+    let mut manager = BarManager::with_writer(&mut out).render_mode(RenderMode::Plain);
+    let second = manager.register(stati::bars::SimpleBar::new(&"second", 100));
+    let first = manager.register_at(stati::bars::SimpleBar::new(&"first", 100), Placement::Before(&second));
+    let total = manager.register_threadsafe_at(stati::bars::SimpleBar::new(&"total", 100), Placement::PinBottom);
+    let third = manager.register(stati::bars::SimpleBar::new(&"third", 100));
+    manager.print();
+}
+let out = String::from_utf8(out).unwrap();
+let names = out.lines().take(4).map(|l| l.split(':').next().unwrap()).collect::<Vec<_>>();
+assert_eq!(names, ["first", "second", "third", "total"]);
+# }
+```
+
+[`print!`]: crate::print
+[`println!`]: crate::print
+[`register_threadsafe`]: BarManager::register_threadsafe
+[`register`]: BarManager::register
+[`register_at`]: BarManager::register_at
+[`register_threadsafe_at`]: BarManager::register_threadsafe_at
+[`spawn_renderer`]: BarManager::spawn_renderer
+[`min_redraw_interval`]: BarManager::min_redraw_interval
+[`plain_interval`]: BarManager::plain_interval
+[`render_mode`]: BarManager::render_mode
+[`colors`]: BarManager::colors
+[`with_writer`]: BarManager::with_writer
+*/
+pub struct BarManager<'bar> {
+    /// all tracked bars, in display order
+    bars: Vec<ManagedBar<'bar>>,
+    out: Output<'bar>,
+}
+
+/// Default for [`BarManager::min_redraw_interval`]
+const DEFAULT_MIN_REDRAW: Duration = Duration::from_millis(50);
+
+/// Default for [`BarManager::plain_interval`]
+const DEFAULT_PLAIN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Indentation added before child bars, for each level of nesting
+const INDENT: &str = "  ";
+
+/// Size hint given to bars with aggregated progress, making their progress a percentage
+const AGGREGATE_SCALE: usize = 100;
+
+/// Environment variable that overrides the detected [`RenderMode`]
+const MODE_ENV_VAR: &str = "STATI_MODE";
+
+/// How a [`BarManager`] draws its bars
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum RenderMode {
+    /// Redraw the bars in place using ANSI escape codes. only works on terminals
+    Interactive,
+    /// Print a plain status line for each bar every so often, and once more when it finishes.
+    /// for output that is not a terminal, like CI logs or files
+    Plain,
+}
+
+impl RenderMode {
+    /// Reads the mode from the `STATI_MODE` environment variable, if it is set to a valid mode
+    fn from_env() -> Option<Self> {
+        let mode = std::env::var(MODE_ENV_VAR).ok()?;
+        match mode.trim().to_ascii_lowercase().as_str() {
+            "plain" => Some(Self::Plain),
+            "interactive" => Some(Self::Interactive),
+            _ => None,
+        }
+    }
+
+    /// Picks the mode for output that may or may not be a terminal, respecting `STATI_MODE`
+    fn detect(is_terminal: bool) -> Self {
+        Self::from_env().unwrap_or(if is_terminal {
+            Self::Interactive
+        } else {
+            Self::Plain
+        })
+    }
+}
+
+/// How many bars ended in each [`FinishState`], see [`BarManager::finish_counts`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct FinishCounts {
+    /// Bars finished with [`done`](crate::IsBar::done), without saying if they worked or not
+    pub done: usize,
+    /// Bars that said their job completed, like with [`finish_with_success`](crate::bars::BundledBar::finish_with_success)
+    pub success: usize,
+    /// Bars that said their job failed, like with [`finish_with_error`](crate::bars::BundledBar::finish_with_error)
+    pub error: usize,
+    /// Bars that were stopped before their job could complete, like with [`abandon`](crate::bars::BundledBar::abandon)
+    pub abandoned: usize,
+}
+
+impl FinishCounts {
+    /// How many bars have finished, in any state
+    #[must_use]
+    pub const fn total(&self) -> usize {
+        self.done + self.success + self.error + self.abandoned
+    }
+
+    fn record(&mut self, state: FinishState) {
+        match state {
+            FinishState::Done => self.done += 1,
+            FinishState::Success => self.success += 1,
+            FinishState::Error => self.error += 1,
+            FinishState::Abandoned => self.abandoned += 1,
+        }
+    }
+}
+
+/// Everything the [`BarManager`] needs for drawing, apart from the bars themselves
+struct Output<'bar> {
+    writer: Box<dyn Write + Send + 'bar>,
+    /// gets the width of the terminal `writer` writes to
+    width: fn() -> Option<u16>,
+    mode: RenderMode,
+    /// if bars can use colors
+    color: bool,
+    color_support: ColorSupport,
+    print_queue: Vec<String>,
+    /// how many rows of the terminal the bars took up the last time they were drawn
+    last_rows: usize,
+    min_redraw: Duration,
+    last_draw: Option<Instant>,
+    /// a redraw was skipped since the last time the bars were drawn
+    dirty: bool,
+    /// set when a bar finishes or is added, so it is drawn right away
+    redraw_signal: RedrawSignal,
+    plain_interval: Duration,
+    /// the last time status lines were printed in [`RenderMode::Plain`]
+    last_status: Option<Instant>,
+    finished: FinishCounts,
+}
+
+impl<'bar> Output<'bar> {
+    fn new(writer: Box<dyn Write + Send + 'bar>, mode: RenderMode) -> Self {
+        Self {
+            writer,
+            width: || None,
+            mode,
+            color: false,
+            color_support: ColorSupport::detect(),
+            print_queue: vec![],
+            last_rows: 0,
+            min_redraw: DEFAULT_MIN_REDRAW,
+            last_draw: None,
+            dirty: false,
+            redraw_signal: RedrawSignal::default(),
+            plain_interval: DEFAULT_PLAIN_INTERVAL,
+            last_status: None,
+            finished: FinishCounts::default(),
+        }
+    }
+}
+
+impl<'bar> BarManager<'bar> {
+    /// Creates a new [`BarManager`] that writes to stdout
+    #[must_use]
+    pub fn new() -> Self {
+        let stdout = std::io::stdout();
+        let mode = RenderMode::detect(stdout.is_terminal());
+        let color = stdout.is_terminal() && crate::style::colors_allowed();
+        let mut manager = Self::with_writer(stdout).render_mode(mode).colors(color);
+        manager.out.width = crate::utils::term_width;
+        manager
+    }
+
+    /// Creates a new [`BarManager`] that writes to stderr,
+    /// leaving stdout free for other output (like data being piped to another program)
+    #[must_use]
+    pub fn with_stderr() -> Self {
+        let stderr = std::io::stderr();
+        let mode = RenderMode::detect(stderr.is_terminal());
+        let color = stderr.is_terminal() && crate::style::colors_allowed();
+        let mut manager = Self::with_writer(stderr).render_mode(mode).colors(color);
+        manager.out.width = crate::utils::stderr_width;
+        manager
+    }
+
+    /// Creates a new [`BarManager`] that writes to `writer` instead of stdout.
+    ///
+    /// this can be anything that implements [`Write`], for example stderr, a file, or a `Vec<u8>` (usefull for testing)
+    ///
+    /// as there is no way to tell if `writer` is a terminal, this uses [`RenderMode::Interactive`]
+    /// unless the `STATI_MODE` environment variable says otherwise. to change this, use [`render_mode`].
+    /// bars are drawn 81 columns wide, without colors (see [`colors`])
+    ///
+    /// [`colors`]: Self::colors
+    ///
+    /// [`render_mode`]: Self::render_mode
+    #[must_use]
+    pub fn with_writer(writer: impl Write + Send + 'bar) -> Self {
+        Self {
+            bars: vec![],
+            out: Output::new(
+                Box::new(writer),
+                RenderMode::from_env().unwrap_or(RenderMode::Interactive),
+            ),
+        }
+    }
+
+    /// Sets how the bars are drawn, overriding the mode that was detected when the manager was created
+    #[must_use]
+    pub fn render_mode(mut self, mode: RenderMode) -> Self {
+        self.out.mode = mode;
+        self
+    }
+
+    /// Sets if bars are drawn with colors, overriding what was detected when the manager was created
+    ///
+    /// this only affects [`RenderMode::Interactive`], as plain status lines never have colors
+    #[must_use]
+    pub fn colors(mut self, color: bool) -> Self {
+        self.out.color = color;
+        self
+    }
+
+    /// Sets how often a status line is printed for each bar in [`RenderMode::Plain`] (5s by default)
+    ///
+    /// status lines are always printed when a bar finishes, regardless of this
+    #[must_use]
+    pub fn plain_interval(mut self, interval: Duration) -> Self {
+        self.out.plain_interval = interval;
+        self
+    }
+
+    /// Sets the minimum amount of time between redraws of the bars (50ms by default)
+    ///
+    /// calls to [`print`] sooner than this after the last redraw will not draw anything,
+    /// unless text has been queued or a bar has been added or finished since then.
+    /// use [`Duration::ZERO`] to redraw on every call
+    ///
+    /// [`print`]: Self::print
+    #[must_use]
+    pub fn min_redraw_interval(mut self, interval: Duration) -> Self {
+        self.out.min_redraw = interval;
+        self
+    }
+
+    /// Registers a progress bar with the bar manager, to be drawn with the manager.
+    /// Returns what is effectively a reference to it, and when that refference is dropped or `.done()` is called,
+    /// the bar is finished, and is completed according to `bar.close_method()`
+    ///
+    /// the bar is displayed after all bars registered before it.
+    /// to register a bar so it can be used across threads, see [`register_threadsafe`]
+    ///
+    /// [`register_threadsafe`]: Self::register_threadsafe
+    pub fn register<B: 'bar + IsBar + Debug>(&mut self, bar: B) -> BarWrapper<B> {
+        self.register_at(bar, Placement::End)
+    }
+
+    /// Like [`register`], however the wrapper returned by this can be used across threads
+    ///
+    /// [`register`]: Self::register
+    pub fn register_threadsafe<B: 'bar + IsBar + Debug + Send>(
+        &mut self,
+        bar: B,
+    ) -> ThreadedBarWrapper<B> {
+        self.register_threadsafe_at(bar, Placement::End)
+    }
+
+    /// Like [`register`], but places the bar according to `at` instead of after all other bars
+    ///
+    /// [`register`]: Self::register
+    pub fn register_at<B: 'bar + IsBar + Debug>(&mut self, bar: B, at: Placement) -> BarWrapper<B> {
+        let wrapped = Rc::new(RefCell::new(bar));
+        let id = BarId::next();
+        self.insert(id, BarRef::Local(wrapped.clone()), at);
+        BarWrapper::new(wrapped, id, self.out.redraw_signal.clone())
+    }
+
+    /// Like [`register_threadsafe`], but places the bar according to `at` instead of after all other bars
+    ///
+    /// [`register_threadsafe`]: Self::register_threadsafe
+    pub fn register_threadsafe_at<B: 'bar + IsBar + Debug + Send>(
+        &mut self,
+        bar: B,
+        at: Placement,
+    ) -> ThreadedBarWrapper<B> {
+        let wrapped = Arc::new(Mutex::new(bar));
+        let id = BarId::next();
+        self.insert(id, BarRef::Threaded(wrapped.clone()), at);
+        ThreadedBarWrapper::new(wrapped, id, self.out.redraw_signal.clone())
+    }
+
+    /// Registers a progress bar as a child of another bar, to be drawn indented below it (and any children it already has).
+    ///
+    /// the child can have children of its own. once a bar and all of its children are done,
+    /// they are collapsed according to the parent's close method: with [`LeaveBehind`] only the parent is left behind,
+    /// and with [`Clear`] they are all cleared.
+    /// to have the parent's progress follow its children, see [`aggregate_children`]
+    ///
+    /// if there is no bar with the id `parent`, or it has already finished along with all of its children
+    /// and been drawn for the last time, the bar is registered normally
+    ///
+    /// ```rust
+    /// use stati::BarManager;
+    ///
+    /// # fn main() {
+    /// let mut manager = BarManager::new();
+    /// let build = manager.register(stati::bars::SimpleBar::new(&"Building", 100));
+    /// let _compile = manager.register_child(build.id(), stati::bars::SimpleBar::new(&"Compiling", 100));
+    /// let _link = manager.register_child(build.id(), stati::bars::SimpleBar::new(&"Linking", 100));
+    /// // displayed as
+    /// // Building
+    /// //   Compiling
+    /// //   Linking
+    /// # }
+    /// ```
+    ///
+    /// [`LeaveBehind`]: BarCloseMethod::LeaveBehind
+    /// [`Clear`]: BarCloseMethod::Clear
+    /// [`aggregate_children`]: Self::aggregate_children
+    pub fn register_child<B: 'bar + IsBar + Debug>(
+        &mut self,
+        parent: BarId,
+        bar: B,
+    ) -> BarWrapper<B> {
+        let wrapped = Rc::new(RefCell::new(bar));
+        let id = BarId::next();
+        self.insert_child(id, BarRef::Local(wrapped.clone()), parent);
+        BarWrapper::new(wrapped, id, self.out.redraw_signal.clone())
+    }
+
+    /// Like [`register_child`], however the wrapper returned by this can be used across threads
+    ///
+    /// [`register_child`]: Self::register_child
+    pub fn register_threadsafe_child<B: 'bar + IsBar + Debug + Send>(
+        &mut self,
+        parent: BarId,
+        bar: B,
+    ) -> ThreadedBarWrapper<B> {
+        let wrapped = Arc::new(Mutex::new(bar));
+        let id = BarId::next();
+        self.insert_child(id, BarRef::Threaded(wrapped.clone()), parent);
+        ThreadedBarWrapper::new(wrapped, id, self.out.redraw_signal.clone())
+    }
+
+    /// Starts tracking `bar` as a top level bar, keeping the bars sorted by section
+    fn insert(&mut self, id: BarId, bar: BarRef<'bar>, at: Placement) {
+        let section_end = |section: Section| {
+            self.bars
+                .iter()
+                .position(|e| e.node.section > section)
+                .unwrap_or(self.bars.len())
+        };
+        // bars placed next to another bar become its sibling
+        let anchored = |anchor: &dyn Anchor, after: bool| {
+            let index = self.index_of(anchor.bar_id())?;
+            let sibling = self.bars[index].node;
+            let index = if after {
+                self.subtree_end(index)
+            } else {
+                index
+            };
+            Some((
+                index,
+                Node::new(id, sibling.section, sibling.parent, sibling.depth),
+            ))
+        };
+        let (index, node) = match at {
+            Placement::Before(anchor) => anchored(anchor, false),
+            Placement::After(anchor) => anchored(anchor, true),
+            Placement::PinTop => Some((
+                section_end(Section::Top),
+                Node::new(id, Section::Top, None, 0),
+            )),
+            Placement::PinBottom => {
+                Some((self.bars.len(), Node::new(id, Section::Bottom, None, 0)))
+            }
+            Placement::End => None,
+        }
+        .unwrap_or_else(|| {
+            (
+                section_end(Section::Middle),
+                Node::new(id, Section::Middle, None, 0),
+            )
+        });
+        let signal = self.out.redraw_signal.clone();
+        signal.notify();
+        self.bars.insert(index, ManagedBar::new(node, bar, signal));
+    }
+
+    /// Starts tracking `bar` as the last child of `parent`
+    fn insert_child(&mut self, id: BarId, bar: BarRef<'bar>, parent: BarId) {
+        // a parent that has already been collapsed has been drawn and counted for the last time,
+        // so it cannot wait for a new child
+        let Some(index) = self
+            .index_of(parent)
+            .filter(|&index| !self.bars[index].node.finished)
+        else {
+            return self.insert(id, bar, Placement::End);
+        };
+        let parent_node = self.bars[index].node;
+        let node = Node::new(id, parent_node.section, Some(parent), parent_node.depth + 1);
+        let end = self.subtree_end(index);
+        let signal = self.out.redraw_signal.clone();
+        signal.notify();
+        self.bars.insert(end, ManagedBar::new(node, bar, signal));
+    }
+
+    fn index_of(&self, id: BarId) -> Option<usize> {
+        self.bars.iter().position(|e| e.node.id == id)
+    }
+
+    /// Index after the last child (or child of a child, etc) of the bar at `index`
+    fn subtree_end(&self, index: usize) -> usize {
+        let depth = self.bars[index].node.depth;
+        index
+            + 1
+            + self.bars[index + 1..]
+                .iter()
+                .take_while(|e| e.node.depth > depth)
+                .count()
+    }
+
+    /// Makes a bar's progress follow the progress of its children (see [`register_child`]),
+    /// setting it every time the bars are drawn.
+    ///
+    /// the parent's progress is set to the weighted average of its childrens progress (see [`set_weight`]) as a percentage,
+    /// using [`IsBar::fraction`] for children that are not done, and counting them as 0% if they do not know.
+    ///
+    /// returns `false` if the bar is no longer tracked
+    ///
+    /// ```rust
+    /// use stati::{BarManager, RenderMode};
+    /// use stati::prelude::*;
+    ///
+    /// # fn main() {
+    /// let mut out = Vec::new();
+    /// {
+    ///     let mut manager = BarManager::with_writer(&mut out).render_mode(RenderMode::Plain);
+    ///     let build = manager.register(stati::bars::SimpleBar::new(&"Building", 100));
+    ///     manager.aggregate_children(&build);
+    ///     let mut compile = manager.register_child(build.id(), stati::bars::SimpleBar::new(&"Compiling", 10));
+    ///     let link = manager.register_child(build.id(), stati::bars::SimpleBar::new(&"Linking", 10));
+    ///     manager.set_weight(link.id(), 3.0);
+    ///     compile.bar().set_progress(10);
+    ///     manager.print();
+    /// }
+    /// assert!(String::from_utf8(out).unwrap().starts_with(
+    ///     "Building: 25% (25/100)\n  Compiling: 100% (10/10)\n  Linking: 0% (0/10)\n"
+    /// ));
+    /// # }
+    /// ```
+    ///
+    /// [`register_child`]: Self::register_child
+    /// [`set_weight`]: Self::set_weight
+    pub fn aggregate_children<B: 'bar + IsBar + IteratorProgress>(
+        &mut self,
+        parent: &BarWrapper<B>,
+    ) -> bool {
+        let Some(index) = self.index_of(parent.id()) else {
+            return false;
+        };
+        self.bars[index].aggregate = Some(ProgressRef::Local(parent.shared().clone()));
+        true
+    }
+
+    /// Like [`aggregate_children`], but for bars registered with [`register_threadsafe`]
+    ///
+    /// [`aggregate_children`]: Self::aggregate_children
+    /// [`register_threadsafe`]: Self::register_threadsafe
+    pub fn aggregate_children_threadsafe<B: 'bar + IsBar + IteratorProgress + Send>(
+        &mut self,
+        parent: &ThreadedBarWrapper<B>,
+    ) -> bool {
+        let Some(index) = self.index_of(parent.id()) else {
+            return false;
+        };
+        self.bars[index].aggregate = Some(ProgressRef::Threaded(parent.shared().clone()));
+        true
+    }
+
+    /// Sets how much a bar counts towards the progress of its parent, if the parent's progress
+    /// is aggregated (see [`aggregate_children`]). bars have a weight of 1 by default
+    ///
+    /// returns `false` if there is no bar with this id
+    ///
+    /// [`aggregate_children`]: Self::aggregate_children
+    pub fn set_weight(&mut self, id: BarId, weight: f64) -> bool {
+        let Some(index) = self.index_of(id) else {
+            return false;
+        };
+        self.bars[index].node.weight = weight;
+        true
+    }
+
+    /// Sets the progress of all aggregated bars from their children.
+    /// goes from the bottom up, so children with aggregated progress of their own are updated first
+    fn update_aggregates(&self) {
+        for (index, entry) in self.bars.iter().enumerate().rev() {
+            let Some(aggregate) = &entry.aggregate else {
+                continue;
+            };
+            let (mut total, mut weights) = (0.0, 0.0);
+            for child in &self.bars[index + 1..self.subtree_end(index)] {
+                if child.node.depth != entry.node.depth + 1 {
+                    continue;
+                }
+                let fraction = child.bar.with(|bar| {
+                    // the child is only drawn after this, so pick up its counter first
+                    bar.sync();
+                    if bar.is_done() {
+                        1.0
+                    } else {
+                        bar.fraction().unwrap_or(0.0).clamp(0.0, 1.0)
+                    }
+                });
+                total += fraction * child.node.weight;
+                weights += child.node.weight;
+            }
+            if weights > 0.0 {
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let progress = (total / weights * AGGREGATE_SCALE as f64).round() as usize;
+                aggregate.with(|bar| {
+                    bar.set_size_hint(AGGREGATE_SCALE);
+                    bar.set_progress(progress);
+                });
+            }
+        }
+    }
+
+    /// Gets a bar tracked by the manager.
+    ///
+    /// returns `None` if there is no bar with this id, or it has finished and is no longer tracked
+    #[must_use]
+    pub fn get(&self, id: BarId) -> Option<&ManagedBar<'bar>> {
+        self.bars.iter().find(|e| e.node.id == id)
+    }
+
+    /// Iterates over all bars tracked by the manager, in the order they are displayed
+    ///
+    /// ```rust
+    /// use stati::BarManager;
+    /// use stati::prelude::*;
+    ///
+    /// # fn main() {
+    /// let mut manager = BarManager::new();
+    /// let mut first = manager.register(stati::bars::SimpleBar::new(&"first", 100));
+    /// let second = manager.register_threadsafe(stati::bars::SimpleBar::new(&"second", 100));
+    /// assert!(manager.iter().map(|bar| bar.id()).eq([first.id(), second.id()]));
+    ///
+    /// manager.move_to(second.id(), 0);
+    /// assert!(manager.iter().map(|bar| bar.id()).eq([second.id(), first.id()]));
+    ///
+    /// manager.get(first.id()).unwrap().done();
+    /// assert!(first.bar().is_done());
+    ///
+    /// manager.remove(second.id());
+    /// assert!(manager.iter().map(|bar| bar.id()).eq([first.id()]));
+    /// # }
+    /// ```
+    pub fn iter(&self) -> std::slice::Iter<'_, ManagedBar<'bar>> {
+        self.bars.iter()
+    }
+
+    /// Stops tracking a bar (and any children it has), removing it from the screen the next time the bars are drawn.
+    /// like a new bar, this is drawn by the next [`print`](Self::print) even inside the [`min_redraw_interval`](Self::min_redraw_interval).
+    /// this does **not** finish the bar, and its wrapper can still be used (it just won't be displayed)
+    ///
+    /// returns the removed bar, or `None` if there is no bar with this id
+    pub fn remove(&mut self, id: BarId) -> Option<ManagedBar<'bar>> {
+        let index = self.index_of(id)?;
+        let end = self.subtree_end(index);
+        self.out.redraw_signal.notify();
+        self.bars.drain(index..end).next()
+    }
+
+    /// Moves a bar (along with its children) so it is at `index` among the bars with the same parent,
+    /// or among the top level bars if it has no parent
+    ///
+    /// bars pinned to the top or bottom (see [`Placement`]) stay pinned,
+    /// so `index` only counts bars in the same group.
+    /// if `index` is past the last bar, the bar is moved to the end.
+    /// the new order is drawn by the next [`print`](Self::print), the same as with [`remove`](Self::remove).
+    /// returns `false` if there is no bar with this id
+    pub fn move_to(&mut self, id: BarId, index: usize) -> bool {
+        let Some(from) = self.index_of(id) else {
+            return false;
+        };
+        let end = self.subtree_end(from);
+        let subtree = self.bars.drain(from..end).collect::<Vec<_>>();
+        let node = subtree[0].node;
+        let siblings = self
+            .bars
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.node.parent == node.parent && e.node.section == node.section)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        let to = match (siblings.get(index), siblings.last()) {
+            (Some(&sibling), _) => sibling,
+            (None, Some(&last)) => self.subtree_end(last),
+            // no siblings, so it goes directly after its parent, or at the start of its section
+            (None, None) => match node.parent {
+                Some(parent) => self.index_of(parent).map_or(0, |i| i + 1),
+                None => self
+                    .bars
+                    .iter()
+                    .take_while(|e| e.node.section < node.section)
+                    .count(),
+            },
+        };
+        self.bars.splice(to..to, subtree);
+        self.out.redraw_signal.notify();
+        true
+    }
+
+    /// Formats the current progress bars, along with the text as messages
+    /// that have been printed in this time, to a string.
+    ///
+    /// this assumes that nothing has been written to the output in the time since it was last called, and as such
+    /// you should not use `std::println!` or `std::print!` with this, and instead `stati::println!` or `stati::print!`
+    ///
+    /// # Panics
+    /// if it cannot borrow any of the contained bars
+    #[must_use]
+    pub(crate) fn display(&mut self) -> String {
+        match self.out.mode {
+            RenderMode::Interactive => self.display_interactive(),
+            RenderMode::Plain => self.display_plain(),
+        }
+    }
+
+    /// [`display`](Self::display) for [`RenderMode::Interactive`], redrawing the bars in place
+    fn display_interactive(&mut self) -> String {
+        let mut res = String::new();
+        // ESC CSI n F (move to the start of the line n lines up)
+        // (this is to overwrite previous bars)
+        if self.out.last_rows != 0 {
+            res += &format!("\x1b[{}F", self.out.last_rows);
+        }
+        // ESC CSI 0 J (clears from cursor to end of screen)
+        res += "\x1b[0J";
+        // print stuff
+        for item in self.out.print_queue.drain(..) {
+            res += &item;
+        }
+        self.update_aggregates();
+        let width = self.width();
+        // go through all bars, removing ones that are done.
+        // finished bars that are left behind go above the rest, so they are not overwritten next time
+        let mut live = String::new();
+        let mut i = 0;
+        while i < self.bars.len() {
+            let node = self.bars[i].node;
+            let ctx = RenderContext::new(indented_width(width, node.depth))
+                .with_color(self.out.color)
+                .with_color_support(self.out.color_support);
+            match self.finish_subtree(i) {
+                Some((BarCloseMethod::Clear, _)) => {
+                    self.bars.remove(i);
+                    continue;
+                }
+                Some((BarCloseMethod::LeaveBehind, _)) if node.parent.is_none() => {
+                    res += &self.bars.remove(i).bar.with(|bar| bar.display_with(&ctx));
+                    res += "\n";
+                    continue;
+                }
+                // finished children stay with their parent until it is finished too
+                _ => {}
+            }
+            live += &indent(
+                &self.bars[i].bar.with(|bar| bar.display_with(&ctx)),
+                node.depth,
+            );
+            live += "\n";
+            i += 1;
+        }
+        self.out.last_rows = rows(&live, width);
+        res += &live;
+        res
+    }
+
+    /// [`display`](Self::display) for [`RenderMode::Plain`], printing text as-is
+    /// and status lines for the bars only when they are due or finished
+    fn display_plain(&mut self) -> String {
+        let mut res = self.out.print_queue.drain(..).collect::<String>();
+        let status_due = self
+            .out
+            .last_status
+            .is_none_or(|last| last.elapsed() >= self.out.plain_interval);
+        if status_due {
+            self.out.last_status = Some(Instant::now());
+        }
+        self.update_aggregates();
+        let mut i = 0;
+        while i < self.bars.len() {
+            let node = self.bars[i].node;
+            let finished = self.finish_subtree(i);
+            let print = finished.map_or(status_due, |(_, newly_finished)| newly_finished);
+            if print {
+                res += &indent(
+                    &self.bars[i].bar.with(|bar| bar.display_plain()),
+                    node.depth,
+                );
+                res += "\n";
+            }
+            match finished {
+                Some((close_method, _))
+                    if close_method == BarCloseMethod::Clear || node.parent.is_none() =>
+                {
+                    self.bars.remove(i);
+                }
+                _ => i += 1,
+            }
+        }
+        res
+    }
+
+    /// Width of the terminal being written to
+    fn width(&self) -> usize {
+        (self.out.width)().map_or(DEFAULT_WIDTH, usize::from)
+    }
+
+    /// If the bar at `index` and all of its children are done, collapses them into it.
+    ///
+    /// returns how the bar should be closed, and if it was just finished (it was not already collapsed the last time this was called)
+    fn finish_subtree(&mut self, index: usize) -> Option<(BarCloseMethod, bool)> {
+        let end = self.subtree_end(index);
+        if !self.bars[index..end]
+            .iter()
+            .all(|e| e.bar.with(|bar| bar.is_done()))
+        {
+            return None;
+        }
+        // children that finished earlier have already been counted
+        for entry in &mut self.bars[index..end] {
+            if !entry.node.finished {
+                self.out
+                    .finished
+                    .record(entry.bar.with(|bar| bar.finish_state()));
+            }
+        }
+        self.bars.drain(index + 1..end);
+        let entry = &mut self.bars[index];
+        let newly_finished = !entry.node.finished;
+        entry.node.finished = true;
+        Some((entry.bar.with(|bar| bar.close_method()), newly_finished))
+    }
+
+    /// How many bars have ended in each [`FinishState`] so far
+    ///
+    /// bars are counted the first time they are drawn after finishing (with [`print`](Self::print)),
+    /// and bars that are [removed](Self::remove) are not counted
+    ///
+    /// ```rust
+    /// use stati::bars::SimpleBar;
+    /// use stati::prelude::*;
+    /// use stati::{BarManager, RenderMode};
+    ///
+    /// # fn main() {
+    /// let mut out = Vec::new();
+    /// let mut manager = BarManager::with_writer(&mut out).render_mode(RenderMode::Plain);
+    /// let mut upload = manager.register(SimpleBar::new("upload", 10));
+    /// let mut download = manager.register(SimpleBar::new("download", 10));
+    /// let mut cleanup = manager.register(SimpleBar::new("cleanup", 10));
+    /// upload.bar().finish_with_success("sent 10 files");
+    /// download.bar().finish_with_error("connection reset");
+    /// cleanup.bar().abandon();
+    /// drop((upload, download, cleanup));
+    /// manager.print();
+    ///
+    /// let counts = manager.finish_counts();
+    /// assert_eq!((counts.success, counts.error, counts.abandoned), (1, 1, 1));
+    /// drop(manager);
+    /// assert_eq!(
+    ///     String::from_utf8(out).unwrap(),
+    ///     "✔ upload: sent 10 files (0s)\n✘ download: connection reset (0s)\n⊘ cleanup: abandoned at 0% (0s)\n"
+    /// );
+    /// # }
+    /// ```
+    #[must_use]
+    pub const fn finish_counts(&self) -> FinishCounts {
+        self.out.finished
+    }
+
+    /// Attempts to flush the output, returning if it was sucsessfull or not
+    ///
+    /// # Errors
+    /// if the output could not be flushed
+    pub fn try_flush(&mut self) -> std::io::Result<()> {
+        self.out.writer.flush()
+    }
+
+    /// Flushes updates to the output.
+    ///
+    /// Currently this only flushes the output, but will hopefully do more in the future
+    ///
+    /// # Panics
+    /// if the output cannot be flushed
+    ///
+    /// for a non-panicing alternative, see [`BarManager::try_flush`]
+    pub fn flush(&mut self) {
+        self.try_flush().unwrap();
+    }
+
+    /// Queues text to be printed before the bars. this should NOT be use
+    /// directly, but should be used with the println! and print! macros
+    ///
+    /// this does NOT immediataly print the text
+    pub fn queue_text(&mut self, text: &str) {
+        self.out.print_queue.push(text.into());
+    }
+
+    /// Prints the bar status and any queued text to the output, and flushes it.
+    ///
+    /// if the bars were drawn less than [`min_redraw_interval`] ago, they are only marked as needing a redraw
+    ///
+    /// # Panics
+    /// if the output cannot be written to or flushed
+    ///
+    /// for a non-panicing alternative, see [`BarManager::try_print`]
+    ///
+    /// [`min_redraw_interval`]: Self::min_redraw_interval
+    pub fn print(&mut self) {
+        self.try_print().unwrap();
+    }
+
+    /// Attempts to print and flush the output
+    ///
+    /// # Errors
+    /// if the output could not be written to or flushed
+    pub fn try_print(&mut self) -> std::io::Result<()> {
+        self.print_no_flush()?;
+        self.try_flush()
+    }
+
+    /// Prints the bar status and any queued text to the output, without flushing it
+    ///
+    /// # Errors
+    /// if the output could not be written to
+    pub fn print_no_flush(&mut self) -> std::io::Result<()> {
+        if self.should_redraw() {
+            self.redraw()
+        } else {
+            self.out.dirty = true;
+            Ok(())
+        }
+    }
+
+    /// Checks if enough time has passed since the last redraw, or if something needs to be drawn right away
+    fn should_redraw(&self) -> bool {
+        let interval_passed = self
+            .out
+            .last_draw
+            .is_none_or(|last| last.elapsed() >= self.out.min_redraw);
+        interval_passed || self.has_pending()
+    }
+
+    /// Checks if there is queued text, or a new or finished bar, that has not been drawn yet
+    fn has_pending(&self) -> bool {
+        !self.out.print_queue.is_empty() || self.out.redraw_signal.is_set()
+    }
+
+    /// Draws the bars and queued text, ignoring [`min_redraw_interval`](Self::min_redraw_interval)
+    fn redraw(&mut self) -> std::io::Result<()> {
+        // cleared first, so bars finishing while the others are drawn are drawn next time
+        self.out.redraw_signal.clear();
+        let text = self.display();
+        self.out.last_draw = Some(Instant::now());
+        self.out.dirty = false;
+        self.out.writer.write_all(text.as_bytes())
+    }
+}
+
+impl BarManager<'static> {
+    /// Moves the manager onto a background thread that redraws all bars every `tick`,
+    /// so that bars updated from other threads keep moving even while this one is busy.
+    /// the bars are drawn every `tick` even if it is shorter than [`min_redraw_interval`](Self::min_redraw_interval)
+    ///
+    /// The returned [`RenderHandle`] can register more threadsafe bars and print text.
+    /// When it is dropped (or [`RenderHandle::stop`] is called) the thread is stopped,
+    /// after drawing the bars one final time.
+    ///
+    /// # Errors
+    /// if any bars created with [`register`] are still being tracked, as they cannot be sent to another thread.
+    /// use [`register_threadsafe`] for bars that should be drawn by the renderer.
+    /// the manager can be taken back out of the error with [`LocalBarsError::into_manager`]
+    ///
+    /// [`register`]: Self::register
+    /// [`register_threadsafe`]: Self::register_threadsafe
+    pub fn spawn_renderer(self, tick: Duration) -> Result<RenderHandle, LocalBarsError> {
+        RenderHandle::spawn(self, tick)
+    }
+}
+
+impl<'bar> Debug for BarManager<'bar> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BarManager")
+            .field("bars", &self.bars)
+            .field("print_queue", &self.out.print_queue)
+            .field("last_rows", &self.out.last_rows)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'bar> Drop for BarManager<'bar> {
+    fn drop(&mut self) {
+        if self.out.dirty || self.has_pending() {
+            // nowhere to report errors to, and panicking in drop is worse than a stale bar
+            let _ = self.redraw().and_then(|()| self.try_flush());
+        }
+    }
+}
+
+impl<'bar> Default for BarManager<'bar> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Width left for a bar nested `depth` levels deep, after indenting it
+fn indented_width(width: usize, depth: usize) -> usize {
+    width.saturating_sub(INDENT.len() * depth).max(1)
+}
+
+/// Indents every line of `text` for a bar nested `depth` levels deep,
+/// keeping carriage returns at the start of lines in front of the indentation
+fn indent(text: &str, depth: usize) -> String {
+    if depth == 0 {
+        return text.into();
+    }
+    let indent = INDENT.repeat(depth);
+    text.split('\n')
+        .map(|line| match line.strip_prefix('\r') {
+            Some(line) => format!("\r{indent}{line}"),
+            None => format!("{indent}{line}"),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// How many rows of a terminal `width` columns wide `text` takes up, counting lines that wrap around
+fn rows(text: &str, width: usize) -> usize {
+    text.lines()
+        .map(|line| {
+            let line_width = crate::utils::display_width(&crate::utils::strip_ansi(line));
+            line_width.div_ceil(width.max(1)).max(1)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use super::{BarManager, RenderMode};
+    use crate::bars::SimpleBar;
+    use crate::prelude::*;
+
+    /// Output that can still be read while the manager is writing to it
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl SharedBuf {
+        fn len(&self) -> usize {
+            self.0.lock().unwrap().len()
+        }
+    }
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// A manager that will not redraw on its own again during the test, once it has drawn
+    fn throttled(out: &SharedBuf) -> BarManager<'static> {
+        BarManager::with_writer(out.clone())
+            .render_mode(RenderMode::Interactive)
+            .min_redraw_interval(Duration::from_secs(3600))
+    }
+
+    #[test]
+    fn aggregate_sees_counter_updates() {
+        let out = SharedBuf::default();
+        let mut manager = BarManager::with_writer(out.clone()).render_mode(RenderMode::Plain);
+        let build = manager.register(SimpleBar::new("Building", 100));
+        manager.aggregate_children(&build);
+        let mut compile = manager.register_child(build.id(), SimpleBar::new("Compiling", 10));
+        compile.bar().counter().set_pos(10);
+        manager.print();
+        let drawn = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+        assert!(
+            drawn.starts_with("Building: 100% (100/100)\n  Compiling: 100% (10/10)\n"),
+            "{drawn:?}"
+        );
+    }
+
+    #[test]
+    fn print_inside_interval_is_skipped() {
+        let out = SharedBuf::default();
+        let mut manager = throttled(&out);
+        let mut bar = manager.register(SimpleBar::new("bar", 100));
+        manager.print();
+        let drawn = out.len();
+        assert!(drawn > 0);
+
+        bar.bar().set_progress(50);
+        manager.print();
+        assert_eq!(out.len(), drawn);
+    }
+
+    #[test]
+    fn print_after_interval_redraws() {
+        let out = SharedBuf::default();
+        let mut manager = BarManager::with_writer(out.clone())
+            .render_mode(RenderMode::Interactive)
+            .min_redraw_interval(Duration::from_millis(10));
+        let mut bar = manager.register(SimpleBar::new("bar", 100));
+        manager.print();
+        let drawn = out.len();
+
+        bar.bar().set_progress(50);
+        std::thread::sleep(Duration::from_millis(20));
+        manager.print();
+        assert!(out.len() > drawn);
+    }
+
+    #[test]
+    fn queued_text_forces_redraw() {
+        let out = SharedBuf::default();
+        let mut manager = throttled(&out);
+        let _bar = manager.register(SimpleBar::new("bar", 100));
+        manager.print();
+        let drawn = out.len();
+
+        crate::println!(manager, "hello");
+        assert!(out.len() > drawn);
+    }
+
+    #[test]
+    fn new_bar_forces_redraw() {
+        let out = SharedBuf::default();
+        let mut manager = throttled(&out);
+        let first = manager.register(SimpleBar::new("first", 100));
+        manager.print();
+        let drawn = out.len();
+
+        let _child = manager.register_child(first.id(), SimpleBar::new("child", 100));
+        manager.print();
+        let drawn_child = out.len();
+        assert!(drawn_child > drawn);
+
+        let _second = manager.register_threadsafe(SimpleBar::new("second", 100));
+        manager.print();
+        assert!(out.len() > drawn_child);
+    }
+
+    #[test]
+    fn finish_forces_redraw() {
+        let out = SharedBuf::default();
+        let mut manager = throttled(&out);
+        let mut first = manager.register(SimpleBar::new("first", 100));
+        let second = manager.register_threadsafe(SimpleBar::new("second", 100));
+        manager.print();
+        let drawn = out.len();
+
+        // finished through the wrapper
+        first.bar().done();
+        manager.print();
+        let drawn_first = out.len();
+        assert!(drawn_first > drawn);
+
+        // updating a bar that is already done does not count as finishing it again
+        first.bar().set_progress(10);
+        manager.print();
+        assert_eq!(out.len(), drawn_first);
+
+        // finished by dropping the wrapper
+        drop(second);
+        manager.print();
+        assert!(out.len() > drawn_first);
+    }
+
+    #[test]
+    fn skipped_redraw_is_drawn_on_drop() {
+        let out = SharedBuf::default();
+        let mut manager = throttled(&out);
+        let mut bar = manager.register(SimpleBar::new("bar", 100));
+        manager.print();
+        bar.bar().set_progress(50);
+        manager.print();
+        let drawn = out.len();
+
+        // the bar is still alive, so it is not finished when the manager is dropped
+        drop(manager);
+        assert!(out.len() > drawn);
+        drop(bar);
+    }
+
+    #[test]
+    fn finished_parent_does_not_take_new_children() {
+        let out = SharedBuf::default();
+        let mut manager = BarManager::with_writer(out.clone()).render_mode(RenderMode::Plain);
+        let mut root = manager.register(SimpleBar::new("root", 100));
+        let mut parent = manager.register_child(root.id(), SimpleBar::new("parent", 100));
+        parent.bar().done();
+        manager.print();
+        assert_eq!(manager.finish_counts().total(), 1);
+
+        let mut child = manager.register_child(parent.id(), SimpleBar::new("child", 100));
+        let managed = manager.iter().find(|bar| bar.id() == child.id()).unwrap();
+        assert_eq!(managed.parent(), None);
+
+        child.bar().done();
+        root.bar().done();
+        manager.print();
+        // the parent is only counted (and printed) once
+        assert_eq!(manager.finish_counts().total(), 3);
+        let printed = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(printed.matches("parent").count(), 1);
+    }
+}
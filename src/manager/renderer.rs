@@ -0,0 +1,327 @@
+#[cfg(feature = "fairness")]
+use parking_lot::FairMutex as Mutex;
+#[cfg(not(feature = "fairness"))]
+use parking_lot::Mutex;
+use std::fmt::{self, Debug};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use super::{BarId, BarManager, BarRef, ManagedBar, Output, Placement, ProgressRef, RenderMode};
+use crate::isbar::{IsBar, IsBarManagerInterface};
+use crate::wrapper::{RedrawSignal, ThreadedBarWrapper};
+
+/// Messages sent from a [`RenderHandle`] to its render thread
+enum Command {
+    Register(Arc<Mutex<dyn IsBarManagerInterface + Send>>, BarId),
+    Remove(BarId),
+    MoveTo(BarId, usize),
+    Text(String),
+    Print,
+    Stop,
+}
+
+/**
+Handle to a [`BarManager`] that is drawing its bars from a background thread,
+created with [`BarManager::spawn_renderer`]
+
+bars registered through this are drawn at a fixed rate, even if every other thread is blocked.
+text can be printed with the [`println!`] and [`print!`] macros, the same as with a [`BarManager`]
+
+when this is dropped, the render thread draws the bars one last time and is stopped.
+to find out if anything went wrong while drawing, use [`stop`] instead.
+
+## Example
+
+```rust
+use std::thread;
+use std::time::Duration;
+
+use stati::BarManager;
+use stati::prelude::*;
+
+# fn main() {
+let renderer = BarManager::new().spawn_renderer(Duration::from_millis(50)).unwrap();
+let mut bar = renderer.register_threadsafe(stati::bars::SimpleBar::new(&"Working...", 100));
+let worker = thread::spawn(move || {
+    for i in 0..=100 {
+        bar.bar().set_progress(i);
+        thread::sleep(Duration::from_millis(5));
+    }
+});
+worker.join().unwrap();
+stati::println!(renderer, "finished working");
+renderer.stop().unwrap();
+# }
+```
+
+[`print!`]: crate::print
+[`println!`]: crate::println
+[`stop`]: RenderHandle::stop
+*/
+#[derive(Debug)]
+pub struct RenderHandle {
+    sender: Sender<Command>,
+    /// the render thread's manager's, for bars registered through this
+    signal: RedrawSignal,
+    thread: Option<JoinHandle<std::io::Result<()>>>,
+}
+
+impl RenderHandle {
+    pub(super) fn spawn(
+        mut manager: BarManager<'static>,
+        tick: Duration,
+    ) -> Result<Self, LocalBarsError> {
+        let has_local = manager.bars.iter().any(|entry| {
+            matches!(entry.bar, BarRef::Local(_))
+                || matches!(entry.aggregate, Some(ProgressRef::Local(_)))
+        });
+        if has_local {
+            return Err(LocalBarsError(Box::new(manager)));
+        }
+        // the manager itself cannot be sent (it could hold single threaded bars), so move everything else out of it
+        let bars = std::mem::take(&mut manager.bars)
+            .into_iter()
+            .filter_map(|entry| match (entry.bar, entry.aggregate) {
+                (BarRef::Threaded(bar), None) => Some((entry.node, bar, None)),
+                (BarRef::Threaded(bar), Some(ProgressRef::Threaded(aggregate))) => {
+                    Some((entry.node, bar, Some(aggregate)))
+                }
+                // checked above
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let out = std::mem::replace(
+            &mut manager.out,
+            Output::new(Box::new(std::io::sink()), RenderMode::Interactive),
+        );
+        let signal = out.redraw_signal.clone();
+        let (sender, receiver) = mpsc::channel();
+        let thread_signal = signal.clone();
+        let thread = thread::spawn(move || {
+            let mut manager = BarManager {
+                bars: bars
+                    .into_iter()
+                    .map(|(node, bar, aggregate)| ManagedBar {
+                        node,
+                        bar: BarRef::Threaded(bar),
+                        aggregate: aggregate.map(ProgressRef::Threaded),
+                        signal: thread_signal.clone(),
+                    })
+                    .collect(),
+                out,
+            };
+            let mut next_draw = Instant::now() + tick;
+            loop {
+                match receiver.recv_timeout(next_draw.saturating_duration_since(Instant::now())) {
+                    // changes to which bars there are show right away, instead of waiting for the next tick
+                    Ok(Command::Register(bar, id)) => {
+                        manager.insert(id, BarRef::Threaded(bar), Placement::End);
+                        manager.try_print()?;
+                    }
+                    Ok(Command::Remove(id)) => {
+                        manager.remove(id);
+                        manager.try_print()?;
+                    }
+                    Ok(Command::MoveTo(id, index)) => {
+                        manager.move_to(id, index);
+                        manager.try_print()?;
+                    }
+                    Ok(Command::Text(text)) => manager.queue_text(&text),
+                    Ok(Command::Print) => manager.try_print()?,
+                    Ok(Command::Stop) | Err(RecvTimeoutError::Disconnected) => break,
+                    Err(RecvTimeoutError::Timeout) => {
+                        // the tick is the redraw rate asked for, so it is not throttled by `min_redraw_interval`
+                        manager.redraw()?;
+                        manager.try_flush()?;
+                        next_draw = Instant::now() + tick;
+                    }
+                }
+            }
+            manager.redraw()?;
+            manager.try_flush()
+        });
+        Ok(Self {
+            sender,
+            signal,
+            thread: Some(thread),
+        })
+    }
+
+    /// Registers a progress bar to be drawn by the render thread,
+    /// the same as [`BarManager::register_threadsafe`]
+    pub fn register_threadsafe<B: 'static + IsBar + Debug + Send>(
+        &self,
+        bar: B,
+    ) -> ThreadedBarWrapper<B> {
+        let wrapped = Arc::new(Mutex::new(bar));
+        let id = BarId::next();
+        // if the render thread has stopped, there is nothing to draw the bar anyway
+        let _ = self.sender.send(Command::Register(wrapped.clone(), id));
+        ThreadedBarWrapper::new(wrapped, id, self.signal.clone())
+    }
+
+    /// Stops drawing a bar (and any children it has), the same as [`BarManager::remove`].
+    /// the bar is not given back, and nothing happens if there is no bar with this id
+    pub fn remove(&self, id: BarId) {
+        let _ = self.sender.send(Command::Remove(id));
+    }
+
+    /// Moves a bar (along with its children), the same as [`BarManager::move_to`]
+    pub fn move_to(&self, id: BarId, index: usize) {
+        let _ = self.sender.send(Command::MoveTo(id, index));
+    }
+
+    /// Queues text to be printed before the bars. this should NOT be use
+    /// directly, but should be used with the println! and print! macros
+    ///
+    /// this does NOT immediataly print the text
+    pub fn queue_text(&self, text: &str) {
+        let _ = self.sender.send(Command::Text(text.into()));
+    }
+
+    /// Asks the render thread to draw the bars and any queued text now,
+    /// instead of waiting for the next tick
+    pub fn print(&self) {
+        let _ = self.sender.send(Command::Print);
+    }
+
+    /// Stops the render thread, after drawing the bars one final time
+    ///
+    /// # Errors
+    /// if the render thread failed to write to or flush its output at any point
+    ///
+    /// # Panics
+    /// if the render thread panicked
+    pub fn stop(mut self) -> std::io::Result<()> {
+        self.shutdown().map_or(Ok(()), |res| {
+            res.unwrap_or_else(|e| std::panic::resume_unwind(e))
+        })
+    }
+
+    fn shutdown(&mut self) -> Option<thread::Result<std::io::Result<()>>> {
+        let thread = self.thread.take()?;
+        let _ = self.sender.send(Command::Stop);
+        Some(thread.join())
+    }
+}
+
+impl Drop for RenderHandle {
+    fn drop(&mut self) {
+        let _ = self.shutdown();
+    }
+}
+
+/// The error returned by [`BarManager::spawn_renderer`] when the manager still has bars created with [`register`],
+/// which cannot be drawn from another thread. the manager is given back unchanged
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use stati::BarManager;
+///
+/// # fn main() {
+/// let mut manager = BarManager::with_writer(std::io::sink());
+/// let bar = manager.register(stati::bars::SimpleBar::new(&"local", 100));
+/// let err = manager.spawn_renderer(Duration::from_millis(50)).unwrap_err();
+/// // finish with the local bar on this thread
+/// let mut manager = err.into_manager();
+/// drop(bar);
+/// manager.print();
+/// # }
+/// ```
+///
+/// [`register`]: BarManager::register
+#[derive(Debug)]
+pub struct LocalBarsError(Box<BarManager<'static>>);
+
+impl LocalBarsError {
+    /// Gets the manager back
+    #[must_use]
+    pub fn into_manager(self) -> BarManager<'static> {
+        *self.0
+    }
+}
+
+impl fmt::Display for LocalBarsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(
+            "bars created with `register` cannot be drawn from another thread, use `register_threadsafe` instead",
+        )
+    }
+}
+
+impl std::error::Error for LocalBarsError {}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::mpsc::{self, Sender};
+    use std::time::{Duration, Instant};
+
+    use crate::bars::SimpleBar;
+    use crate::BarManager;
+
+    /// Sends everything written between flushes, which is one draw
+    struct SendDraws(Vec<u8>, Sender<String>);
+
+    impl Write for SendDraws {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            let draw = String::from_utf8_lossy(&std::mem::take(&mut self.0)).into_owned();
+            let _ = self.1.send(draw);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn remove_and_move_are_drawn_right_away() {
+        let (sender, draws) = mpsc::channel();
+        // neither the tick nor the throttle will draw anything during the test
+        let renderer = BarManager::with_writer(SendDraws(Vec::new(), sender))
+            .min_redraw_interval(Duration::from_secs(3600))
+            .spawn_renderer(Duration::from_secs(3600))
+            .unwrap();
+        let next_draw = || draws.recv_timeout(Duration::from_secs(10)).unwrap();
+        let first = renderer.register_threadsafe(SimpleBar::new("first", 100));
+        assert!(next_draw().contains("first"));
+        let second = renderer.register_threadsafe(SimpleBar::new("second", 100));
+        assert!(next_draw().contains("second"));
+
+        renderer.move_to(second.id(), 0);
+        let draw = next_draw();
+        assert!(draw.find("second").unwrap() < draw.find("first").unwrap());
+
+        renderer.remove(first.id());
+        let draw = next_draw();
+        assert!(draw.contains("second") && !draw.contains("first"));
+        drop((first, second));
+    }
+
+    #[test]
+    fn tick_is_not_throttled() {
+        let (sender, draws) = mpsc::channel();
+        // the throttle would not allow a second draw for an hour, so every draw after the first is a tick
+        let renderer = BarManager::with_writer(SendDraws(Vec::new(), sender))
+            .min_redraw_interval(Duration::from_secs(3600))
+            .spawn_renderer(Duration::from_millis(1))
+            .unwrap();
+        let bar = renderer.register_threadsafe(SimpleBar::new("ticking", 100));
+        // a tick can come before the bar is registered, so only draws with the bar are counted
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut drawn = 0;
+        while drawn < 5 {
+            let draw = draws
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                .unwrap();
+            drawn += usize::from(draw.contains("ticking"));
+        }
+        drop(bar);
+        renderer.stop().unwrap();
+    }
+}
@@ -0,0 +1,135 @@
+//! Progress bars for rayon's parallel iterators, like [`iterator`](crate::iterator) is for normal iterators
+//!
+//! only available with the `rayon` feature
+//!
+//! all worker threads count up the bar's [`ProgressCounter`], which the bar reads when it is drawn,
+//! so threads never wait on each other, or on the bar, just to count an item
+//!
+//! ```rust
+//! use rayon::prelude::*;
+//!
+//! use stati::bars::SimpleBar;
+//! use stati::prelude::*;
+//! use stati::{BarManager, RenderMode};
+//!
+//! # fn main() {
+//! let mut manager = BarManager::with_writer(std::io::sink()).render_mode(RenderMode::Plain);
+//! let mut bar = manager.register_threadsafe(SimpleBar::new("Summing", 0));
+//! let sum: u64 = (0..1000u64)
+//!     .into_par_iter()
+//!     .display_bar(bar.clone())
+//!     .map(|i| i * 2)
+//!     .sum();
+//! assert_eq!(sum, 999_000);
+//! // the total came from the iterator's length
+//! assert_eq!(bar.bar().display_plain(), "Summing: 100% (1000/1000)");
+//! # }
+//! ```
+
+use rayon::iter::plumbing::{Consumer, ProducerCallback, UnindexedConsumer};
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+
+use crate::subsets::CounterProgress;
+use crate::{IsBar, ProgressCounter, ThreadedBarWrapper};
+
+/// A parallel iterator that shows how many items have come out of it on a bar, created with [`display_bar`]
+///
+/// if the iterator knows its length (it is an [`IndexedParallelIterator`], or [`opt_len`] is `Some`), that is used as the bar's size hint
+///
+/// [`display_bar`]: ParallelProgressAdaptor::display_bar
+/// [`opt_len`]: ParallelIterator::opt_len
+#[derive(Debug)]
+pub struct ProgressParIter<I, B: IsBar> {
+    base: I,
+    bar: ThreadedBarWrapper<B>,
+}
+
+/// Starts counting from 0 on the bar's counter, setting its length to `len` (an unknown length is 0).
+/// this is the only time the bar is locked
+fn start<B: IsBar + CounterProgress>(
+    bar: &ThreadedBarWrapper<B>,
+    len: Option<usize>,
+) -> ProgressCounter {
+    let counter = bar.shared().lock().counter();
+    counter.set_pos(0);
+    counter.set_len(len.unwrap_or(0));
+    counter
+}
+
+impl<I, B> ParallelIterator for ProgressParIter<I, B>
+where
+    I: ParallelIterator,
+    B: IsBar + CounterProgress + Send,
+{
+    type Item = I::Item;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        let counter = start(&self.bar, self.base.opt_len());
+        self.base
+            .map(|item| {
+                counter.inc(1);
+                item
+            })
+            .drive_unindexed(consumer)
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        self.base.opt_len()
+    }
+}
+
+impl<I, B> IndexedParallelIterator for ProgressParIter<I, B>
+where
+    I: IndexedParallelIterator,
+    B: IsBar + CounterProgress + Send,
+{
+    fn drive<C>(self, consumer: C) -> C::Result
+    where
+        C: Consumer<Self::Item>,
+    {
+        let counter = start(&self.bar, Some(self.base.len()));
+        self.base
+            .map(|item| {
+                counter.inc(1);
+                item
+            })
+            .drive(consumer)
+    }
+
+    fn len(&self) -> usize {
+        self.base.len()
+    }
+
+    fn with_producer<CB>(self, callback: CB) -> CB::Output
+    where
+        CB: ProducerCallback<Self::Item>,
+    {
+        let counter = start(&self.bar, Some(self.base.len()));
+        self.base
+            .map(|item| {
+                counter.inc(1);
+                item
+            })
+            .with_producer(callback)
+    }
+}
+
+pub trait ParallelProgressAdaptor: ParallelIterator {
+    /// Takes control of a progress bar, displaying how many items have come out of the parallel iterator.
+    ///
+    /// this needs a [`ThreadedBarWrapper`] (from [`register_threadsafe`]), since the bar is updated from all of rayon's worker threads.
+    /// the bar is only locked once, to get its [`CounterProgress::counter`], and the items are counted on that
+    ///
+    /// [`register_threadsafe`]: crate::BarManager::register_threadsafe
+    fn display_bar<B>(self, bar: ThreadedBarWrapper<B>) -> ProgressParIter<Self, B>
+    where
+        B: IsBar + CounterProgress + Send,
+    {
+        ProgressParIter { base: self, bar }
+    }
+}
+
+impl<I: ParallelIterator> ParallelProgressAdaptor for I {}
@@ -7,7 +7,13 @@
 //! if you want to use a trait yourself, and not just its methods,
 //! import it seperatly
 
+pub use crate::bars::BundledBar as __stati_BundledBar;
+pub use crate::isbar::subsets::CounterProgress as __stati_CounterProgress;
 pub use crate::isbar::subsets::IteratorProgress as __stati_IteratorProgress;
 pub use crate::iterator::ProgressTrackingAdaptor as __stati_ProgressTrackingAdaptor;
+#[cfg(feature = "rayon")]
+pub use crate::par_iter::ParallelProgressAdaptor as __stati_ParallelProgressAdaptor;
+#[cfg(feature = "async")]
+pub use crate::stream::ProgressStreamAdaptor as __stati_ProgressStreamAdaptor;
 pub use crate::wrapper::IsBarWrapper as __stati_IsBarWrapper;
 pub use crate::IsBar as __stati_IsBar;
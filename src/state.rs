@@ -0,0 +1,498 @@
+//! Shared progress tracking for bars
+//!
+//! [`ProgressState`] keeps track of everything about a job's progress that is not how it is drawn:
+//! when it started, how far along it is, and how fast it is going.
+//! the bundled bars all use it, and it can be used by your own [`IsBar`] implementations as well
+//!
+//! [`IsBar`]: crate::IsBar
+
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+
+use crate::clock::{Clock, SharedClock};
+use crate::counter::ProgressCounter;
+
+/// How far back [`ProgressState::rate`] looks by default
+pub const DEFAULT_RATE_WINDOW: Duration = Duration::from_secs(5);
+
+/// How long a job has to have been running before [`ProgressState::rate`] reports anything.
+/// any sooner, and a single update can make it look absurdly fast
+pub const MIN_RATE_ELAPSED: Duration = Duration::from_millis(500);
+
+/// How far along a job is, and how long it has been running for.
+///
+/// jobs can have an unknown length, by giving them a length of 0.
+/// once they are given a length that is not 0, they switch to having a known length
+///
+/// ```rust
+/// use stati::ProgressState;
+/// use stati::format::HumanDuration;
+///
+/// # fn main() {
+/// let mut state = ProgressState::new(100);
+/// state.set_pos(25);
+/// assert_eq!(state.percentage(), 25);
+/// // no time has passed, so there is no way to tell how long is left
+/// let eta = state.eta().map_or("?".into(), |eta| HumanDuration(eta).to_string());
+/// println!("{}/{} {} elapsed, {} left", state.pos(), state.len().unwrap(), HumanDuration(state.elapsed()), eta);
+///
+/// let mut unknown = ProgressState::new(0);
+/// unknown.set_pos(25);
+/// assert_eq!(unknown.len(), None);
+/// assert_eq!(unknown.fraction(), None);
+/// unknown.set_len(50);
+/// assert_eq!(unknown.percentage(), 50);
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ProgressState {
+    started: Instant,
+    pos: usize,
+    /// `None` if the length is unknown
+    len: Option<usize>,
+    window: Duration,
+    /// exponentially weighted moving average of the rate, as of `last_update`.
+    /// this starts at 0, so it is too low until enough time has passed (see `rate`)
+    ewma: f64,
+    last_update: Instant,
+    /// how long the job ran for, once it has been stopped
+    stopped: Option<Duration>,
+    counter: Option<Linked>,
+    clock: SharedClock,
+}
+
+/// A counter attached to a [`ProgressState`], and what it was at the last [`sync`](ProgressState::sync)
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+struct Linked {
+    counter: ProgressCounter,
+    pos: usize,
+    len: Option<usize>,
+    generation: u64,
+}
+
+impl ProgressState {
+    /// Creates a new [`ProgressState`] for a job that is `len` items long (or of unknown length, if it is 0), starting now
+    #[must_use]
+    pub fn new(len: usize) -> Self {
+        let clock = SharedClock::default();
+        let now = clock.now();
+        Self {
+            started: now,
+            pos: 0,
+            len: None,
+            window: DEFAULT_RATE_WINDOW,
+            ewma: 0.0,
+            last_update: now,
+            stopped: None,
+            counter: None,
+            clock,
+        }
+        .with_len(len)
+    }
+
+    /// Uses `clock` to tell the time instead of the system clock, starting the job from `clock`'s current time.
+    ///
+    /// this is mostly useful for testing (see [`clock`](crate::clock))
+    #[must_use]
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.set_clock(SharedClock::new(clock));
+        self
+    }
+
+    /// Switches to `clock`, starting the job again from its current time
+    pub(crate) fn set_clock(&mut self, clock: SharedClock) {
+        let now = clock.now();
+        (self.started, self.last_update, self.clock) = (now, now, clock);
+    }
+
+    fn with_len(mut self, len: usize) -> Self {
+        self.set_len(len);
+        self
+    }
+
+    /// Sets the current position (how many items are done)
+    pub fn set_pos(&mut self, pos: usize) {
+        let now = self.clock.now();
+        self.ewma = self.ewma_at(now, pos);
+        self.last_update = now;
+        self.pos = pos;
+    }
+
+    /// Sets how far back [`rate`] looks. progress made `window` ago counts for about a third as much as progress made now.
+    ///
+    /// a shorter window reacts faster to changes in speed, and a longer one is steadier.
+    /// the default is [`DEFAULT_RATE_WINDOW`]
+    ///
+    /// [`rate`]: Self::rate
+    pub fn set_window(&mut self, window: Duration) {
+        self.window = window;
+    }
+
+    /// How far back [`rate`](Self::rate) looks, see [`set_window`](Self::set_window)
+    #[must_use]
+    pub const fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Adds `delta` to the current position
+    pub fn inc(&mut self, delta: usize) {
+        self.set_pos(self.pos.saturating_add(delta));
+    }
+
+    /// Sets how many items there are in total. if this is 0, the length is unknown
+    pub fn set_len(&mut self, len: usize) {
+        self.len = (len != 0).then_some(len);
+    }
+
+    /// Restarts the job, setting the position to 0 and the start time to now
+    pub fn reset(&mut self) {
+        if let Some(linked) = &mut self.counter {
+            linked.counter.set_pos(0);
+            linked.pos = 0;
+        }
+        let now = self.clock.now();
+        *self = Self {
+            started: now,
+            pos: 0,
+            ewma: 0.0,
+            last_update: now,
+            stopped: None,
+            counter: self.counter.take(),
+            clock: self.clock.clone(),
+            ..*self
+        };
+    }
+
+    /// Gets a [`ProgressCounter`] for updating this without a `&mut` reference, attaching one if there is not one already.
+    ///
+    /// the counter starts at the current position and length, and its changes are applied by [`sync`](Self::sync).
+    /// setting the position or length here directly still works, but is overwritten the next time the counter changes
+    pub fn counter(&mut self) -> ProgressCounter {
+        let linked = self.counter.get_or_insert_with(|| {
+            let counter = ProgressCounter::new(self.len.unwrap_or(0));
+            counter.set_pos(self.pos);
+            Linked {
+                generation: counter.message_generation(),
+                counter,
+                pos: self.pos,
+                len: self.len,
+            }
+        });
+        linked.counter.clone()
+    }
+
+    /// Applies any changes made through the attached [`counter`](Self::counter). bars call this before they are drawn.
+    ///
+    /// returns the counter's message, if it was set since the last sync
+    ///
+    /// ```rust
+    /// use stati::ProgressState;
+    ///
+    /// # fn main() {
+    /// let mut state = ProgressState::new(10);
+    /// let counter = state.counter();
+    /// counter.inc(4);
+    /// counter.set_message("halfway-ish");
+    /// assert_eq!(state.pos(), 0);
+    /// assert_eq!(state.sync().as_deref(), Some("halfway-ish"));
+    /// assert_eq!(state.pos(), 4);
+    /// assert_eq!(state.sync(), None);
+    /// # }
+    /// ```
+    pub fn sync(&mut self) -> Option<String> {
+        let linked = self.counter.as_mut()?;
+        let (pos, len, generation) = (
+            linked.counter.pos(),
+            linked.counter.len(),
+            linked.counter.message_generation(),
+        );
+        let message = (generation != linked.generation).then(|| linked.counter.message());
+        let changed_pos = pos != linked.pos;
+        let changed_len = len != linked.len;
+        (linked.pos, linked.len, linked.generation) = (pos, len, generation);
+        if changed_len {
+            self.set_len(len.unwrap_or(0));
+        }
+        if changed_pos {
+            self.set_pos(pos);
+        }
+        message
+    }
+
+    /// The current position (how many items are done)
+    #[must_use]
+    pub const fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// How many items there are in total, or `None` if it is unknown
+    #[must_use]
+    #[allow(clippy::len_without_is_empty)] // this is not a collection
+    pub const fn len(&self) -> Option<usize> {
+        self.len
+    }
+
+    /// When the job started
+    #[must_use]
+    pub const fn started(&self) -> Instant {
+        self.started
+    }
+
+    /// How long the job has been running for, or how long it ran for if it has been [stopped](Self::stop)
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.stopped
+            .unwrap_or_else(|| self.since_start(self.clock.now()))
+    }
+
+    /// Stops the clock, so [`elapsed`](Self::elapsed) stays the same from now on. used when a job is finished
+    ///
+    /// stopping a job that is already stopped does nothing, and [`reset`](Self::reset) starts it again
+    pub fn stop(&mut self) {
+        let elapsed = self.since_start(self.clock.now());
+        self.stopped.get_or_insert(elapsed);
+    }
+
+    /// How far along the job is, from `0.0` to `1.0`. `None` if the length is unknown
+    #[must_use]
+    pub fn fraction(&self) -> Option<f64> {
+        self.len.map(|len| (self.pos as f64 / len as f64).min(1.0))
+    }
+
+    /// How far along the job is, from 0 to 100. if the length is unknown, this is 0
+    #[must_use]
+    pub fn percentage(&self) -> usize {
+        self.len
+            .map_or(0, |len| (self.pos.saturating_mul(100) / len).min(100))
+    }
+
+    /// How many items are being done per second.
+    ///
+    /// this is an exponentially weighted moving average, so recent progress counts for more than older progress
+    /// (see [`set_window`]), and it goes down while no progress is being made.
+    /// it does not depend on how often the bar is drawn or updated.
+    ///
+    /// until the job has been running for [`MIN_RATE_ELAPSED`], there is not enough to go on, so this is 0
+    ///
+    /// [`set_window`]: Self::set_window
+    #[must_use]
+    pub fn rate(&self) -> f64 {
+        let now = self.clock.now();
+        let elapsed = self.since_start(now);
+        if elapsed < MIN_RATE_ELAPSED {
+            return 0.0;
+        }
+        // the average starts at 0, so divide out how much of its weight is from before the job started.
+        // until a full window has passed, this is close to the average rate since the start
+        let weight = 1.0 - (-self.windows(elapsed)).exp();
+        if weight > 0.0 {
+            self.ewma_at(now, self.pos) / weight
+        } else {
+            0.0
+        }
+    }
+
+    /// How long it has been since the job started, as of `now`
+    fn since_start(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.started)
+    }
+
+    /// The moving average, updated for the position being `pos` at `now`
+    fn ewma_at(&self, now: Instant, pos: usize) -> f64 {
+        let dt = now.saturating_duration_since(self.last_update);
+        if dt.is_zero() {
+            return self.ewma;
+        }
+        // going backwards does not count as negative progress
+        let current = pos.saturating_sub(self.pos) as f64 / dt.as_secs_f64();
+        let alpha = 1.0 - (-self.windows(dt)).exp();
+        self.ewma + alpha * (current - self.ewma)
+    }
+
+    /// How many windows long `duration` is
+    fn windows(&self, duration: Duration) -> f64 {
+        duration.as_secs_f64() / self.window.as_secs_f64()
+    }
+
+    /// Roughly how long is left until the job is finished, based on [`rate`].
+    ///
+    /// `None` if nothing has been done yet, the length is unknown, or it is too soon to tell how fast it is going
+    ///
+    /// [`rate`]: Self::rate
+    #[must_use]
+    pub fn eta(&self) -> Option<Duration> {
+        let len = self.len?;
+        let rate = self.rate();
+        if self.pos == 0 || !rate.is_normal() {
+            return None;
+        }
+        let left = len.saturating_sub(self.pos) as f64;
+        Duration::try_from_secs_f64(left / rate).ok()
+    }
+}
+
+impl ProgressState {
+    /// Everything that makes two states the same, with the moving average compared bit for bit
+    #[allow(clippy::type_complexity)]
+    fn key(
+        &self,
+    ) -> (
+        Instant,
+        usize,
+        Option<usize>,
+        Duration,
+        u64,
+        Instant,
+        Option<Duration>,
+        &Option<Linked>,
+        &SharedClock,
+    ) {
+        (
+            self.started,
+            self.pos,
+            self.len,
+            self.window,
+            self.ewma.to_bits(),
+            self.last_update,
+            self.stopped,
+            &self.counter,
+            &self.clock,
+        )
+    }
+}
+
+impl PartialEq for ProgressState {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for ProgressState {}
+
+impl Hash for ProgressState {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl crate::subsets::IteratorProgress for ProgressState {
+    fn set_progress(&mut self, progress: usize) {
+        self.set_pos(progress);
+    }
+
+    fn set_size_hint(&mut self, hint: usize) {
+        self.set_len(hint);
+    }
+}
+
+impl crate::subsets::CounterProgress for ProgressState {
+    fn counter(&mut self) -> ProgressCounter {
+        Self::counter(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{ProgressState, MIN_RATE_ELAPSED};
+    use crate::clock::ManualClock;
+
+    /// Makes progress at `rate` items per second for `secs` seconds, in steps of 100ms
+    fn run(state: &mut ProgressState, clock: &ManualClock, rate: usize, secs: usize) {
+        for _ in 0..secs * 10 {
+            clock.advance(Duration::from_millis(100));
+            state.inc(rate / 10);
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() <= expected * 0.02,
+            "rate was {actual}, expected about {expected}"
+        );
+    }
+
+    #[test]
+    fn steady_rate() {
+        let clock = ManualClock::new();
+        let mut state = ProgressState::new(10_000).with_clock(clock.clone());
+        run(&mut state, &clock, 100, 30);
+        assert_close(state.rate(), 100.0);
+        // 7000 left at 100/s
+        let eta = state.eta().unwrap();
+        assert!((eta.as_secs_f64() - 70.0).abs() < 2.0, "eta was {eta:?}");
+    }
+
+    #[test]
+    fn rate_follows_changes_in_speed() {
+        let clock = ManualClock::new();
+        let mut state = ProgressState::new(0).with_clock(clock.clone());
+        run(&mut state, &clock, 100, 30);
+        run(&mut state, &clock, 400, 30);
+        assert_close(state.rate(), 400.0);
+    }
+
+    #[test]
+    fn rate_decays_while_idle() {
+        let clock = ManualClock::new();
+        let mut state = ProgressState::new(0).with_clock(clock.clone());
+        run(&mut state, &clock, 100, 30);
+        // one window with no progress leaves about a third of the rate
+        clock.advance(state.window());
+        assert_close(state.rate(), 100.0 * (-1.0f64).exp());
+        clock.advance(state.window() * 10);
+        assert!(state.rate() < 0.01);
+    }
+
+    #[test]
+    fn early_estimate_is_held_back() {
+        let clock = ManualClock::new();
+        let mut state = ProgressState::new(10_000).with_clock(clock.clone());
+        clock.advance(Duration::from_millis(5));
+        state.set_pos(1000);
+        assert_eq!(state.rate(), 0.0);
+        assert_eq!(state.eta(), None);
+
+        // once there is enough to go on, it is the average since the start
+        clock.advance(MIN_RATE_ELAPSED);
+        let elapsed = state.elapsed().as_secs_f64();
+        let rate = state.rate();
+        assert!(rate > 0.0 && rate <= 1000.0 / elapsed, "rate was {rate}");
+        assert!(state.eta().is_some());
+    }
+
+    #[test]
+    fn elapsed_uses_clock() {
+        let clock = ManualClock::new();
+        let mut state = ProgressState::new(10).with_clock(clock.clone());
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(state.elapsed(), Duration::from_secs(3));
+        state.stop();
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(state.elapsed(), Duration::from_secs(3));
+        state.reset();
+        assert_eq!(state.elapsed(), Duration::ZERO);
+    }
+
+    #[test]
+    fn eq_and_hash_follow_progress() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let hash = |state: &ProgressState| {
+            let mut hasher = DefaultHasher::new();
+            state.hash(&mut hasher);
+            hasher.finish()
+        };
+        let clock = ManualClock::new();
+        let mut state = ProgressState::new(100).with_clock(clock.clone());
+        let copy = state.clone();
+        assert_eq!(state, copy);
+        assert_eq!(hash(&state), hash(&copy));
+
+        clock.advance(Duration::from_secs(1));
+        state.set_pos(10);
+        assert_ne!(state, copy);
+    }
+}
@@ -0,0 +1,115 @@
+//! Progress bars for async [`Stream`]s, like [`iterator`](crate::iterator) is for iterators
+//!
+//! only available with the `async` feature
+//!
+//! ```rust
+//! use futures_util::{stream, StreamExt};
+//!
+//! use stati::bars::SimpleBar;
+//! use stati::prelude::*;
+//! use stati::{BarManager, RenderMode};
+//!
+//! # fn main() {
+//! let mut manager = BarManager::with_writer(std::io::sink()).render_mode(RenderMode::Plain);
+//! let mut bar = manager.register_threadsafe(SimpleBar::new("Streaming", 0));
+//! let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+//! let items = runtime.block_on(async {
+//!     stream::iter(0..10)
+//!         .display_bar(bar.clone())
+//!         .collect::<Vec<_>>()
+//!         .await
+//! });
+//! assert_eq!(items.len(), 10);
+//! assert_eq!(bar.bar().display_plain(), "Streaming: 100% (10/10)");
+//! # }
+//! ```
+
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use futures_core::Stream;
+
+use crate::io::{set_progress, set_size_hint};
+use crate::subsets::IteratorProgress;
+use crate::wrapper::IsBarWrapper;
+
+pin_project_lite::pin_project! {
+    /// A [`Stream`] that shows how many items have come out of it on a bar, created with [`display_bar`]
+    ///
+    /// the size hint of the bar is set from the stream's [`size_hint`](Stream::size_hint)
+    ///
+    /// [`display_bar`]: ProgressStreamAdaptor::display_bar
+    #[derive(Debug)]
+    pub struct ProgressStream<S, W> {
+        #[pin]
+        stream: S,
+        bar: W,
+        items_count: usize,
+        manual_hint: Option<usize>,
+    }
+}
+
+impl<S, W> ProgressStream<S, W> {
+    /// Applies a manual size hint to the progress bar, for streams that do not know how long they are
+    pub fn manual_hint(&mut self, hint: usize) -> &mut Self {
+        self.manual_hint = Some(hint);
+        self
+    }
+}
+
+impl<S, W> Stream for ProgressStream<S, W>
+where
+    S: Stream,
+    W: IsBarWrapper,
+    W::Bar: IteratorProgress,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        let next = ready!(this.stream.as_mut().poll_next(cx));
+        if next.is_some() {
+            *this.items_count += 1;
+        }
+        let (lower, upper) = this.stream.size_hint();
+        let hint = this.manual_hint.unwrap_or_else(|| {
+            // with no upper bound, the lower one is the best guess there is,
+            // and if that is 0 too, the length is unknown (0)
+            let hint = upper.map_or(lower, |upper| lower.max(upper));
+            if hint == 0 && upper.is_none() {
+                0
+            } else {
+                *this.items_count + hint
+            }
+        });
+        set_size_hint(this.bar, hint as u64);
+        set_progress(this.bar, *this.items_count as u64);
+        Poll::Ready(next)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+pub trait ProgressStreamAdaptor: Stream + Sized {
+    /// Takes control of a progress bar, displaying how many items have come out of the stream.
+    ///
+    /// this works best with a [`ThreadedBarWrapper`], so the stream can be used across threads
+    ///
+    /// [`ThreadedBarWrapper`]: crate::ThreadedBarWrapper
+    fn display_bar<W>(self, bar: W) -> ProgressStream<Self, W>
+    where
+        W: IsBarWrapper,
+        W::Bar: IteratorProgress,
+    {
+        ProgressStream {
+            stream: self,
+            bar,
+            items_count: 0,
+            manual_hint: None,
+        }
+    }
+}
+
+impl<S: Stream> ProgressStreamAdaptor for S {}
@@ -0,0 +1,419 @@
+//! Colors and text styles for bars
+//!
+//! the bundled bars take a [`BarStyles`], with a [`Style`] for each part of the bar.
+//! styles are only used when the output is a terminal that supports them:
+//! they are turned off when the `NO_COLOR` environment variable is set, `TERM` is `dumb`,
+//! or the output is not a terminal (see [`RenderContext::color`])
+//!
+//! ```rust
+//! use stati::bars::custom::Builder;
+//! use stati::style::{BarStyles, Color, Style};
+//!
+//! # fn main() {
+//! let bar = Builder::new("Colorful")
+//!     .styles(BarStyles {
+//!         name: Style::new().bold(),
+//!         filled: Style::new().fg(Color::Green),
+//!         empty: Style::new().dim(),
+//!         ..BarStyles::default()
+//!     })
+//!     .build();
+//! # }
+//! ```
+//!
+//! [`RenderContext::color`]: crate::RenderContext::color
+
+/// A terminal color
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+    /// One of the 256 colors in the extended palette
+    Ansi256(u8),
+    /// A 24 bit color
+    Rgb(u8, u8, u8),
+}
+
+/// The 16 basic colors, in the order of their palette index, with the RGB values xterm uses for them
+const BASIC: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::White, (229, 229, 229)),
+    (Color::BrightBlack, (127, 127, 127)),
+    (Color::BrightRed, (255, 0, 0)),
+    (Color::BrightGreen, (0, 255, 0)),
+    (Color::BrightYellow, (255, 255, 0)),
+    (Color::BrightBlue, (92, 92, 255)),
+    (Color::BrightMagenta, (255, 0, 255)),
+    (Color::BrightCyan, (0, 255, 255)),
+    (Color::BrightWhite, (255, 255, 255)),
+];
+
+/// The levels each channel can have in the 6x6x6 color cube of the 256 color palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl Color {
+    /// Converts this color to the closest one the terminal can show
+    ///
+    /// ```rust
+    /// use stati::style::{Color, ColorSupport};
+    ///
+    /// # fn main() {
+    /// let orange = Color::Rgb(255, 135, 0);
+    /// assert_eq!(orange.downsample(ColorSupport::TrueColor), orange);
+    /// assert_eq!(orange.downsample(ColorSupport::Ansi256), Color::Ansi256(208));
+    /// assert_eq!(orange.downsample(ColorSupport::Basic), Color::Yellow);
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn downsample(self, support: ColorSupport) -> Self {
+        match (self, support) {
+            (Self::Rgb(r, g, b), ColorSupport::Ansi256) => Self::Ansi256(rgb_to_256(r, g, b)),
+            (Self::Rgb(r, g, b), ColorSupport::Basic) => nearest_basic((r, g, b)),
+            (Self::Ansi256(n), ColorSupport::Basic) => match BASIC.get(usize::from(n)) {
+                Some(&(color, _)) => color,
+                None => nearest_basic(ansi256_to_rgb(n)),
+            },
+            _ => self,
+        }
+    }
+
+    /// The SGR parameters for this color, as a foreground color if `fg` or background color otherwise
+    fn sgr(self, fg: bool) -> String {
+        let base: u8 = if fg { 30 } else { 40 };
+        // the bright colors are in their own range, 60 above the normal ones
+        let offset = match self {
+            Self::Ansi256(n) => return format!("{};5;{n}", base + 8),
+            Self::Rgb(r, g, b) => return format!("{};2;{r};{g};{b}", base + 8),
+            Self::Black => 0,
+            Self::Red => 1,
+            Self::Green => 2,
+            Self::Yellow => 3,
+            Self::Blue => 4,
+            Self::Magenta => 5,
+            Self::Cyan => 6,
+            Self::White => 7,
+            Self::BrightBlack => 60,
+            Self::BrightRed => 61,
+            Self::BrightGreen => 62,
+            Self::BrightYellow => 63,
+            Self::BrightBlue => 64,
+            Self::BrightMagenta => 65,
+            Self::BrightCyan => 66,
+            Self::BrightWhite => 67,
+        };
+        (base + offset).to_string()
+    }
+}
+
+/// The closest color to an RGB value in the 6x6x6 cube or grayscale ramp of the 256 color palette
+fn rgb_to_256(r: u8, g: u8, b: u8) -> u8 {
+    if r == g && g == b {
+        // the grayscale ramp goes from 8 to 238 in steps of 10, with black and white in the cube
+        return match r {
+            0..=3 => 16,
+            248..=255 => 231,
+            _ => 232 + ((r.max(8) - 8) / 10).min(23),
+        };
+    }
+    let level = |v: u8| {
+        CUBE_LEVELS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, &level)| level.abs_diff(v))
+            .map_or(0, |(i, _)| i as u8)
+    };
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
+
+/// The RGB value of a color from the 256 color palette that is not one of the basic colors
+fn ansi256_to_rgb(n: u8) -> (u8, u8, u8) {
+    if n >= 232 {
+        let gray = 8 + (n - 232) * 10;
+        return (gray, gray, gray);
+    }
+    let n = usize::from(n - 16);
+    (
+        CUBE_LEVELS[n / 36],
+        CUBE_LEVELS[n / 6 % 6],
+        CUBE_LEVELS[n % 6],
+    )
+}
+
+/// The basic color closest to an RGB value
+fn nearest_basic((r, g, b): (u8, u8, u8)) -> Color {
+    let distance = |(r2, g2, b2): (u8, u8, u8)| {
+        [(r, r2), (g, g2), (b, b2)]
+            .iter()
+            .map(|&(a, b)| u32::from(a.abs_diff(b)).pow(2))
+            .sum::<u32>()
+    };
+    BASIC
+        .iter()
+        .min_by_key(|(_, rgb)| distance(*rgb))
+        .map_or(Color::White, |&(color, _)| color)
+}
+
+/// How many colors a terminal can show
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub enum ColorSupport {
+    /// Only the 16 basic colors
+    #[default]
+    Basic,
+    /// The 256 color palette
+    Ansi256,
+    /// Any 24 bit color
+    TrueColor,
+}
+
+impl ColorSupport {
+    /// Guesses what the terminal supports from the `COLORTERM` and `TERM` environment variables
+    #[must_use]
+    pub fn detect() -> Self {
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        let term = std::env::var("TERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Ansi256
+        } else {
+            Self::Basic
+        }
+    }
+}
+
+/// How a piece of text is styled: its colors, and if it is bold or dim.
+///
+/// the default style does nothing
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Style {
+    fg: Option<Color>,
+    bg: Option<Color>,
+    bold: bool,
+    dim: bool,
+}
+
+impl Style {
+    /// Creates a style that does nothing
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            fg: None,
+            bg: None,
+            bold: false,
+            dim: false,
+        }
+    }
+
+    /// Sets the foreground (text) color
+    #[must_use]
+    pub const fn fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self
+    }
+
+    /// Sets the background color
+    #[must_use]
+    pub const fn bg(mut self, color: Color) -> Self {
+        self.bg = Some(color);
+        self
+    }
+
+    /// Makes the text bold
+    #[must_use]
+    pub const fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    /// Makes the text dim
+    #[must_use]
+    pub const fn dim(mut self) -> Self {
+        self.dim = true;
+        self
+    }
+
+    /// Converts the colors of this style to the closest ones the terminal can show, see [`Color::downsample`]
+    #[must_use]
+    pub fn downsample(mut self, support: ColorSupport) -> Self {
+        self.fg = self.fg.map(|color| color.downsample(support));
+        self.bg = self.bg.map(|color| color.downsample(support));
+        self
+    }
+
+    /// Checks if this style does nothing
+    #[must_use]
+    pub const fn is_plain(&self) -> bool {
+        self.fg.is_none() && self.bg.is_none() && !self.bold && !self.dim
+    }
+
+    /// Wraps `text` in the ANSI escape codes for this style, resetting the style after it
+    ///
+    /// ```rust
+    /// use stati::style::{Color, Style};
+    ///
+    /// # fn main() {
+    /// assert_eq!(Style::new().fg(Color::Red).bold().paint("hi"), "\x1b[1;31mhi\x1b[0m");
+    /// assert_eq!(Style::new().paint("hi"), "hi");
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn paint(&self, text: &str) -> String {
+        if self.is_plain() || text.is_empty() {
+            return text.into();
+        }
+        let mut params = vec![];
+        if self.bold {
+            params.push("1".to_string());
+        }
+        if self.dim {
+            params.push("2".to_string());
+        }
+        for (color, fg) in [(self.fg, true), (self.bg, false)] {
+            if let Some(color) = color {
+                params.push(color.sgr(fg));
+            }
+        }
+        format!("\x1b[{}m{text}\x1b[0m", params.join(";"))
+    }
+}
+
+/// Styles for each part of a bar
+///
+/// bars that do not have one of these parts ignore its style
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct BarStyles {
+    /// The name of the job
+    pub name: Style,
+    /// The filled section of the bar
+    pub filled: Style,
+    /// The empty section of the bar
+    pub empty: Style,
+    /// The start and end of the bar (like `[` and `]`)
+    pub brackets: Style,
+    /// The percentage (or count, if the length is unknown)
+    pub percentage: Style,
+    /// A message or subtask
+    pub message: Style,
+}
+
+impl BarStyles {
+    /// These styles if colors are enabled in `ctx`, and plain styles otherwise.
+    /// colors are downsampled to what the terminal supports
+    #[must_use]
+    pub fn for_context(&self, ctx: &crate::RenderContext) -> Self {
+        if !ctx.color {
+            return Self::default();
+        }
+        let support = ctx.color_support;
+        Self {
+            name: self.name.downsample(support),
+            filled: self.filled.downsample(support),
+            empty: self.empty.downsample(support),
+            brackets: self.brackets.downsample(support),
+            percentage: self.percentage.downsample(support),
+            message: self.message.downsample(support),
+        }
+    }
+}
+
+/// A color for the filled section of a bar that changes as the bar fills up
+///
+/// ```rust
+/// use stati::bars::custom::Builder;
+/// use stati::prelude::*;
+/// use stati::style::{Color, ColorSupport, FillColor};
+/// use stati::RenderContext;
+///
+/// # fn main() {
+/// let mut bar = Builder::new("Job")
+///     .fill_color(FillColor::Thresholds(vec![
+///         (0, Color::Red),
+///         (30, Color::Yellow),
+///         (70, Color::Green),
+///     ]))
+///     .hint(100)
+///     .build();
+/// bar.set_progress(50);
+/// let ctx = RenderContext::new(80).with_color(true);
+/// assert!(bar.display_with(&ctx).contains("\x1b[33m="));
+///
+/// let mut bar = Builder::new("Job")
+///     .fill_color(FillColor::Gradient {
+///         from: (255, 0, 0),
+///         to: (0, 255, 0),
+///     })
+///     .hint(100)
+///     .build();
+/// bar.set_progress(50);
+/// let ctx = ctx.with_color_support(ColorSupport::TrueColor);
+/// assert!(bar.display_with(&ctx).contains("\x1b[38;2;128;128;0m="));
+/// # }
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum FillColor {
+    /// Blends from `from` when empty to `to` when full.
+    /// on terminals that do not support 24 bit colors, the closest color they do support is used
+    Gradient {
+        from: (u8, u8, u8),
+        to: (u8, u8, u8),
+    },
+    /// Pairs of a percentage and the color used from that percentage onwards, in any order.
+    /// below the lowest percentage, the filled style is left alone
+    Thresholds(Vec<(u8, Color)>),
+}
+
+impl FillColor {
+    /// The color for a bar that is `fraction` of the way full
+    #[must_use]
+    pub fn color_at(&self, fraction: f64) -> Option<Color> {
+        let fraction = fraction.clamp(0.0, 1.0);
+        match self {
+            Self::Gradient { from, to } => {
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let mix = |a: u8, b: u8| {
+                    (f64::from(a) + (f64::from(b) - f64::from(a)) * fraction).round() as u8
+                };
+                Some(Color::Rgb(
+                    mix(from.0, to.0),
+                    mix(from.1, to.1),
+                    mix(from.2, to.2),
+                ))
+            }
+            Self::Thresholds(thresholds) => thresholds
+                .iter()
+                .filter(|(percent, _)| f64::from(*percent) <= fraction * 100.0)
+                .max_by_key(|(percent, _)| *percent)
+                .map(|&(_, color)| color),
+        }
+    }
+}
+
+/// Checks the environment to see if colors are allowed, going by the `NO_COLOR` and `TERM` environment variables
+///
+/// this does not check if the output is a terminal
+#[must_use]
+pub fn colors_allowed() -> bool {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let dumb = std::env::var_os("TERM").is_some_and(|term| term == "dumb");
+    !no_color && !dumb
+}
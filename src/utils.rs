@@ -1,3 +1,9 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Width used for bars when the size of the terminal is not known
+pub const DEFAULT_WIDTH: usize = 81;
+
 pub fn term_width() -> Option<u16> {
     use terminal_size::{terminal_size, Height, Width};
     let size = terminal_size();
@@ -7,3 +13,116 @@ pub fn term_width() -> Option<u16> {
         None
     }
 }
+
+/// Width of the terminal stderr is connected to, if it is one
+pub fn stderr_width() -> Option<u16> {
+    #[cfg(unix)]
+    let size = {
+        use std::os::unix::io::AsRawFd;
+        terminal_size::terminal_size_using_fd(std::io::stderr().as_raw_fd())
+    };
+    #[cfg(windows)]
+    let size = {
+        use std::os::windows::io::AsRawHandle;
+        terminal_size::terminal_size_using_handle(std::io::stderr().as_raw_handle())
+    };
+    #[cfg(not(any(unix, windows)))]
+    let size = None;
+    size.map(|(terminal_size::Width(w), _)| w)
+}
+
+/// Removes ANSI escape sequences and carriage returns from `text`
+pub fn strip_ansi(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\x1b' => {
+                // CSI sequences (ESC [ ... final byte) run until a byte in the range @..~,
+                // everything else we care about is ESC followed by a single character
+                if chars.next() == Some('[') {
+                    for ch in chars.by_ref() {
+                        if ('@'..='~').contains(&ch) {
+                            break;
+                        }
+                    }
+                }
+            }
+            '\r' => {}
+            ch => res.push(ch),
+        }
+    }
+    res
+}
+
+/// How many columns `text` takes up in a terminal.
+///
+/// this goes by grapheme, so things like emoji made of several characters are counted correctly,
+/// and ANSI escape sequences take up no space
+pub fn display_width(text: &str) -> usize {
+    pieces(text)
+        .map(|(_, piece)| piece.map_or(0, grapheme_width))
+        .sum()
+}
+
+/// How many columns a single grapheme takes up. nothing is wider than 2 columns
+fn grapheme_width(grapheme: &str) -> usize {
+    grapheme.width().min(2)
+}
+
+/// Cuts `text` off after at most `width` columns, without splitting graphemes or escape sequences.
+///
+/// escape sequences after the cut are dropped. if there were any before it, the style is reset at the end
+pub fn truncate_to_width(text: &str, width: usize) -> std::borrow::Cow<'_, str> {
+    let mut used = 0;
+    let mut styled = false;
+    for (i, piece) in pieces(text) {
+        match piece {
+            None => styled = true,
+            Some(grapheme) => {
+                used += grapheme_width(grapheme);
+                if used > width {
+                    let cut = &text[..i];
+                    return if styled {
+                        format!("{cut}\x1b[0m").into()
+                    } else {
+                        cut.into()
+                    };
+                }
+            }
+        }
+    }
+    text.into()
+}
+
+/// Splits `text` into graphemes (`Some`) and ANSI escape sequences (`None`), along with where they start
+fn pieces(text: &str) -> impl Iterator<Item = (usize, Option<&str>)> {
+    let mut rest = text;
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        let len = if rest.starts_with('\x1b') {
+            escape_len(rest)
+        } else {
+            rest.graphemes(true).next()?.len()
+        };
+        let (piece, after) = rest.split_at(len);
+        let start = pos;
+        pos += len;
+        rest = after;
+        Some((start, (!piece.starts_with('\x1b')).then_some(piece)))
+    })
+}
+
+/// Length of the escape sequence at the start of `text`.
+/// CSI sequences (ESC [ ... final byte) run until a byte in the range @..~,
+/// everything else we care about is ESC followed by a single character
+fn escape_len(text: &str) -> usize {
+    let mut chars = text.char_indices().skip(1);
+    match chars.next() {
+        Some((_, '[')) => chars
+            .find(|(_, ch)| ('@'..='~').contains(ch))
+            .map_or(text.len(), |(i, ch)| i + ch.len_utf8()),
+        Some((i, ch)) => i + ch.len_utf8(),
+        None => text.len(),
+    }
+}
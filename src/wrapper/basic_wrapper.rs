@@ -1,9 +1,11 @@
-use super::IsBarWrapper;
+use super::{Guard, IsBarWrapper, RedrawSignal};
 use crate::isbar::IsBar;
+use crate::manager::BarId;
 use core::cell::RefCell;
 use std::cell::BorrowMutError;
 #[cfg(feature = "nightly")]
 use std::cell::RefMut;
+use std::fmt::Debug;
 #[cfg(not(feature = "nightly"))]
 use std::ops::DerefMut;
 use std::rc::Rc;
@@ -18,7 +20,7 @@ use std::rc::Rc;
 /// [`Bar`]: IsBar
 /// [`done`]: IsBar::done
 #[derive(Clone, Debug)]
-pub struct BarWrapper<B: IsBar>(Rc<RefCell<B>>);
+pub struct BarWrapper<B: IsBar>(Rc<RefCell<B>>, BarId, RedrawSignal);
 
 #[cfg(not(feature = "nightly"))]
 impl<B: IsBar> IsBarWrapper for BarWrapper<B> {
@@ -27,7 +29,7 @@ impl<B: IsBar> IsBarWrapper for BarWrapper<B> {
     fn try_bar<'b>(
         &'b mut self,
     ) -> Result<Box<dyn DerefMut<Target = Self::Bar> + 'b>, BorrowMutError> {
-        Ok(Box::new(self.0.try_borrow_mut()?))
+        Ok(Box::new(Guard::new(self.0.try_borrow_mut()?, &self.2)))
     }
 }
 
@@ -36,26 +38,45 @@ impl<B: IsBar> IsBarWrapper for BarWrapper<B> {
     type Bar = B;
     type Error = BorrowMutError;
     type BarGuard<'g>
+        = Guard<'g, RefMut<'g, Self::Bar>>
     where
-        Self: 'g,
-    = RefMut<'g, Self::Bar>;
+        Self: 'g;
     fn try_bar<'g>(&'g mut self) -> Result<Self::BarGuard<'g>, BorrowMutError> {
-        self.0.try_borrow_mut()
+        Ok(Guard::new(self.0.try_borrow_mut()?, &self.2))
     }
 }
 
-impl<B: IsBar> From<Rc<RefCell<B>>> for BarWrapper<B> {
-    fn from(item: Rc<RefCell<B>>) -> Self {
-        Self(item)
+impl<B: IsBar> BarWrapper<B> {
+    pub(crate) fn new(bar: Rc<RefCell<B>>, id: BarId, signal: RedrawSignal) -> Self {
+        Self(bar, id, signal)
+    }
+
+    /// The bar, shared with the manager
+    pub(crate) const fn shared(&self) -> &Rc<RefCell<B>> {
+        &self.0
+    }
+
+    /// The id the bar was given when it was registered with the [`BarManager`]
+    ///
+    /// [`BarManager`]: crate::manager::BarManager
+    #[must_use]
+    pub const fn id(&self) -> BarId {
+        self.1
     }
 }
 
 impl<B: IsBar> Drop for BarWrapper<B> {
     fn drop(&mut self) {
         if let Ok(mut b) = self.0.try_borrow_mut() {
-            b.done();
+            self.2.done(&mut *b);
         }
     }
 }
 
+impl<B: IsBar + Debug> crate::manager::Anchor for BarWrapper<B> {
+    fn bar_id(&self) -> BarId {
+        self.1
+    }
+}
+
 impl<B: IsBar> crate::sealant::Sealed for BarWrapper<B> {}
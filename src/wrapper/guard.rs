@@ -0,0 +1,104 @@
+use std::fmt::Debug;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::IsBar;
+
+/// Set when a bar is finished or added, so its [`BarManager`] draws it right away instead of waiting for the next redraw.
+///
+/// shared between a manager and the wrappers of all its bars, so the manager does not have to check every bar to find out
+///
+/// [`BarManager`]: crate::BarManager
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RedrawSignal(Arc<AtomicBool>);
+
+impl RedrawSignal {
+    pub(crate) fn notify(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub(crate) fn is_set(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn clear(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+
+    /// Calls [`IsBar::done`] on `bar`, notifying the manager if it was not already done
+    pub(crate) fn done(&self, bar: &mut (impl IsBar + ?Sized)) {
+        if !bar.is_done() {
+            bar.done();
+            self.notify();
+        }
+    }
+}
+
+/// Access to a bar through its wrapper, returned by [`IsBarWrapper::try_bar`].
+///
+/// if the bar is finished while this is held, its [`BarManager`] is told when this is dropped
+///
+/// [`IsBarWrapper::try_bar`]: super::IsBarWrapper::try_bar
+/// [`BarManager`]: crate::BarManager
+pub struct Guard<'g, G: DerefMut>
+where
+    G::Target: IsBar,
+{
+    guard: G,
+    was_done: bool,
+    signal: &'g RedrawSignal,
+}
+
+impl<'g, G: DerefMut> Guard<'g, G>
+where
+    G::Target: IsBar,
+{
+    pub(crate) fn new(guard: G, signal: &'g RedrawSignal) -> Self {
+        Self {
+            was_done: guard.is_done(),
+            guard,
+            signal,
+        }
+    }
+}
+
+impl<'g, G: DerefMut> Deref for Guard<'g, G>
+where
+    G::Target: IsBar,
+{
+    type Target = G::Target;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<'g, G: DerefMut> DerefMut for Guard<'g, G>
+where
+    G::Target: IsBar,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+impl<'g, G: DerefMut> Drop for Guard<'g, G>
+where
+    G::Target: IsBar,
+{
+    fn drop(&mut self) {
+        if !self.was_done && self.guard.is_done() {
+            self.signal.notify();
+        }
+    }
+}
+
+impl<'g, G: DerefMut + Debug> Debug for Guard<'g, G>
+where
+    G::Target: IsBar,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.guard.fmt(f)
+    }
+}
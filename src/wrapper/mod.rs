@@ -1,4 +1,5 @@
 mod basic_wrapper;
+mod guard;
 mod threaded_wrapper;
 
 use std::fmt::Debug;
@@ -7,6 +8,8 @@ use std::ops::DerefMut;
 use crate::IsBar;
 
 pub use basic_wrapper::BarWrapper;
+pub use guard::Guard;
+pub(crate) use guard::RedrawSignal;
 pub use threaded_wrapper::ThreadedBarWrapper;
 
 /// General API for a bar wrappers used by [`ThreadedBarWrapper`] and [`BarWrapper`]
@@ -2,10 +2,12 @@
 use parking_lot::FairMutex as Mutex;
 #[cfg(not(feature = "fairness"))]
 use parking_lot::Mutex;
+use std::fmt::Debug;
 use std::sync::Arc;
 
-use super::IsBarWrapper;
+use super::{Guard, IsBarWrapper, RedrawSignal};
 use crate::isbar::IsBar;
+use crate::manager::BarId;
 
 /// a wrapper around a [`Bar`], allowing the manager to keep a copy while
 /// passing one to the user
@@ -20,7 +22,7 @@ use crate::isbar::IsBar;
 /// [`Bar`]: IsBar
 /// [`done`]: IsBar::done
 #[derive(Clone, Debug)]
-pub struct ThreadedBarWrapper<B: IsBar>(Arc<Mutex<B>>);
+pub struct ThreadedBarWrapper<B: IsBar>(Arc<Mutex<B>>, BarId, RedrawSignal);
 
 #[cfg(not(feature = "nightly"))]
 use std::ops::DerefMut;
@@ -30,7 +32,7 @@ impl<B: IsBar> IsBarWrapper for ThreadedBarWrapper<B> {
     type Error = ();
 
     fn try_bar<'b>(&'b mut self) -> Result<Box<dyn DerefMut<Target = Self::Bar> + 'b>, ()> {
-        Ok(Box::new(self.0.lock()))
+        Ok(Box::new(Guard::new(self.0.lock(), &self.2)))
     }
 
     /// Get a reference to the underlying bar.
@@ -47,18 +49,22 @@ impl<B: IsBar> IsBarWrapper for ThreadedBarWrapper<B> {
 }
 
 #[cfg(feature = "nightly")]
-use parking_lot::{lock_api::MutexGuard, RawFairMutex};
+use parking_lot::lock_api::MutexGuard;
+#[cfg(all(feature = "nightly", feature = "fairness"))]
+use parking_lot::RawFairMutex as RawMutex;
+#[cfg(all(feature = "nightly", not(feature = "fairness")))]
+use parking_lot::RawMutex;
 #[cfg(feature = "nightly")]
 impl<B: IsBar> IsBarWrapper for ThreadedBarWrapper<B> {
     type Bar = B;
     type Error = ();
     type BarGuard<'g>
+        = Guard<'g, MutexGuard<'g, RawMutex, Self::Bar>>
     where
-        Self: 'g,
-    = MutexGuard<'g, RawFairMutex, Self::Bar>;
+        Self: 'g;
 
     fn try_bar<'g>(&'g mut self) -> Result<Self::BarGuard<'g>, ()> {
-        Ok(self.0.lock())
+        Ok(Guard::new(self.0.lock(), &self.2))
     }
 
     /// Get a reference to the underlying bar.
@@ -74,15 +80,34 @@ impl<B: IsBar> IsBarWrapper for ThreadedBarWrapper<B> {
     }
 }
 
-impl<B: IsBar> From<Arc<Mutex<B>>> for ThreadedBarWrapper<B> {
-    fn from(item: Arc<Mutex<B>>) -> Self {
-        Self(item)
+impl<B: IsBar> ThreadedBarWrapper<B> {
+    pub(crate) fn new(bar: Arc<Mutex<B>>, id: BarId, signal: RedrawSignal) -> Self {
+        Self(bar, id, signal)
+    }
+
+    /// The bar, shared with the manager
+    pub(crate) const fn shared(&self) -> &Arc<Mutex<B>> {
+        &self.0
+    }
+
+    /// The id the bar was given when it was registered with the [`BarManager`]
+    ///
+    /// [`BarManager`]: crate::manager::BarManager
+    #[must_use]
+    pub const fn id(&self) -> BarId {
+        self.1
     }
 }
 
 impl<B: IsBar> Drop for ThreadedBarWrapper<B> {
     fn drop(&mut self) {
-        self.0.lock().done();
+        self.2.done(&mut *self.0.lock());
+    }
+}
+
+impl<B: IsBar + Debug> crate::manager::Anchor for ThreadedBarWrapper<B> {
+    fn bar_id(&self) -> BarId {
+        self.1
     }
 }
 